@@ -0,0 +1,63 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use small_iter::{IntoSmallIterExt, SmallIter};
+use std::hint::black_box;
+
+const NUM_ELEMENTS: usize = 100_000;
+
+/// Hides every method except `next`, so that the default implementations of
+/// the other methods are used.
+struct NextOnly<I>(I);
+
+impl<I: Iterator> Iterator for NextOnly<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+fn make_small_iter() -> SmallIter<u64> {
+    (0..NUM_ELEMENTS as u64)
+        .collect::<Vec<u64>>()
+        .into_small_iter()
+}
+
+fn make_boxed_small_iter() -> SmallIter<Box<u64>> {
+    (0..NUM_ELEMENTS as u64)
+        .map(Box::new)
+        .collect::<Vec<_>>()
+        .into_small_iter()
+}
+
+/// Compares `SmallIter` against `NextOnly<SmallIter>` for the same method.
+/// Construction is excluded from the measurement.
+macro_rules! bench_method {
+    ($c:expr, $name:expr, $make:expr, |$iter:pat_param| $body:expr) => {{
+        let mut group = $c.benchmark_group($name);
+        group.bench_function(BenchmarkId::new("small_iter", ""), |b| {
+            b.iter_batched($make, |$iter| black_box($body), BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("next_only", ""), |b| {
+            b.iter_batched(
+                || NextOnly($make()),
+                |$iter| black_box($body),
+                BatchSize::LargeInput,
+            )
+        });
+        group.finish();
+    }};
+}
+
+fn bench_fast_paths(c: &mut Criterion) {
+    bench_method!(c, "nth", make_small_iter, |mut iter| iter
+        .nth(NUM_ELEMENTS - 1));
+    bench_method!(c, "nth_boxed", make_boxed_small_iter, |mut iter| iter
+        .nth(NUM_ELEMENTS - 1));
+    bench_method!(c, "last", make_small_iter, |iter| iter.last());
+    bench_method!(c, "max", make_small_iter, |iter| iter.max());
+    bench_method!(c, "fold", make_small_iter, |iter| iter
+        .fold(0_u64, |acc, x| acc.wrapping_add(x)));
+}
+
+criterion_group!(benches, bench_fast_paths);
+criterion_main!(benches);
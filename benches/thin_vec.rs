@@ -0,0 +1,57 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use small_iter::{IntoSmallIterExt, SmallIter};
+use std::hint::black_box;
+use thin_vec::ThinVec;
+
+fn make_thin_vec(len: usize) -> ThinVec<u64> {
+    (0..len as u64).collect()
+}
+
+// Converting a `ThinVec` into a `SmallIter`, directly and by collecting into
+// a `Vec` first. Construction of the `ThinVec` is excluded from the
+// measurement.
+fn bench_into_small_iter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("thin_vec_into_small_iter");
+    for len in [16, 1024, 100_000] {
+        group.bench_function(BenchmarkId::new("direct", len), |b| {
+            b.iter_batched(
+                || make_thin_vec(len),
+                |vec| black_box(vec.into_small_iter()),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("via_vec", len), |b| {
+            b.iter_batched(
+                || make_thin_vec(len),
+                |vec| black_box(vec.into_iter().collect::<Vec<u64>>().into_small_iter()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+// Converting a `SmallIter` back into a `ThinVec`, directly and by collecting.
+fn bench_from_small_iter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("small_iter_into_thin_vec");
+    for len in [16, 1024, 100_000] {
+        group.bench_function(BenchmarkId::new("direct", len), |b| {
+            b.iter_batched(
+                || make_thin_vec(len).into_small_iter(),
+                |iter| black_box(ThinVec::from(iter)),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("collect", len), |b| {
+            b.iter_batched(
+                || make_thin_vec(len).into_small_iter(),
+                |iter: SmallIter<u64>| black_box(iter.collect::<ThinVec<u64>>()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_into_small_iter, bench_from_small_iter);
+criterion_main!(benches);
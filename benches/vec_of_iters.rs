@@ -1,5 +1,5 @@
-use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use small_iter::{IntoSmallIterExt, SmallIter};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use small_iter::{IntoSmallIterExt, SmallIter, ThinSmallIter};
 use std::hint::black_box;
 use std::{iter, vec};
 use thin_vec::ThinVec;
@@ -15,15 +15,34 @@ fn consume<I: Iterator<Item = u8>>(mut iters: Vec<I>) {
     }
 }
 
-fn using_small_iter() {
-    let iters: Vec<SmallIter<u8>> = iter::repeat_with(|| {
+fn consume_first<I: Iterator<Item = u8>>(mut iters: Vec<I>) {
+    for iter in &mut iters {
+        black_box(iter.next());
+    }
+}
+
+fn make_small_iters() -> Vec<SmallIter<u8>> {
+    iter::repeat_with(|| {
         (0..(NUM_ELEMENTS as u8))
             .collect::<Vec<u8>>()
             .into_small_iter()
     })
     .take(NUM_ITERS)
-    .collect();
-    consume(black_box(iters));
+    .collect()
+}
+
+fn make_thin_small_iters() -> Vec<ThinSmallIter<u8>> {
+    iter::repeat_with(|| ThinSmallIter::from((0..(NUM_ELEMENTS as u8)).collect::<Vec<u8>>()))
+        .take(NUM_ITERS)
+        .collect()
+}
+
+fn using_small_iter() {
+    consume(black_box(make_small_iters()));
+}
+
+fn using_thin_small_iter() {
+    consume(black_box(make_thin_small_iters()));
 }
 
 fn using_thin_vec_into_iter() {
@@ -50,6 +69,9 @@ fn bench_vec_of_iters(c: &mut Criterion) {
     group.bench_function(BenchmarkId::new("using_small_iter", ""), |b| {
         b.iter(using_small_iter)
     });
+    group.bench_function(BenchmarkId::new("using_thin_small_iter", ""), |b| {
+        b.iter(using_thin_small_iter)
+    });
     group.bench_function(BenchmarkId::new("using_thin_vec_into_iter", ""), |b| {
         b.iter(using_thin_vec_into_iter)
     });
@@ -59,5 +81,18 @@ fn bench_vec_of_iters(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_vec_of_iters);
+// Iterators that are stored, but mostly left untouched. Construction is
+// excluded from the measurement.
+fn bench_first_of_iters(c: &mut Criterion) {
+    let mut group = c.benchmark_group("first_of_iters");
+    group.bench_function(BenchmarkId::new("using_small_iter", ""), |b| {
+        b.iter_batched(make_small_iters, consume_first, BatchSize::LargeInput)
+    });
+    group.bench_function(BenchmarkId::new("using_thin_small_iter", ""), |b| {
+        b.iter_batched(make_thin_small_iters, consume_first, BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_vec_of_iters, bench_first_of_iters);
 criterion_main!(benches);
@@ -0,0 +1,231 @@
+//! Derive macro for the [`small_iter`](https://docs.rs/small_iter) crate.
+//!
+//! Don't depend on this crate directly. Instead, enable the `derive` feature
+//! of `small_iter`, and use `small_iter::IntoSmallIter`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, token, Attribute, Data, DeriveInput, Error,
+    Field, Index, Member, Result, Token, Type,
+};
+
+/// Implements `IntoSmallIterExt` for a struct by forwarding to one of its
+/// fields.
+///
+/// If the struct has exactly one field, that field is used. Otherwise, mark
+/// the field with `#[small_iter(field)]`, or select it on the struct with
+/// `#[small_iter(field = name)]`, or `#[small_iter(field = 0)]` for tuple
+/// structs. The type of the field must implement `IntoSmallIterExt`.
+///
+/// With `#[small_iter(into_iter)]`, this also implements `IntoIterator`, with
+/// `SmallIter` as the iterator.
+///
+/// ```
+/// use small_iter::{IntoSmallIter, IntoSmallIterExt};
+///
+/// #[derive(IntoSmallIter)]
+/// struct Names(Vec<String>);
+///
+/// #[derive(IntoSmallIter)]
+/// #[small_iter(into_iter)]
+/// struct Ids {
+///     name: String,
+///     #[small_iter(field)]
+///     ids: Box<[u32]>,
+/// }
+///
+/// let mut names = Names(vec!["a".to_string()]).into_small_iter();
+/// assert_eq!(names.next().as_deref(), Some("a"));
+///
+/// let ids = Ids { name: "b".to_string(), ids: Box::new([1, 2]) };
+/// let ids: Vec<u32> = ids.into_iter().collect();
+/// assert_eq!(ids, [1, 2]);
+/// ```
+///
+/// Selecting more than one field is an error:
+///
+/// ```compile_fail
+/// use small_iter::IntoSmallIter;
+///
+/// #[derive(IntoSmallIter)]
+/// #[small_iter(field = 0)]
+/// struct Pair(Vec<u8>, #[small_iter(field)] Vec<u8>);
+/// ```
+///
+/// So is anything else in `#[small_iter(...)]` on a field:
+///
+/// ```compile_fail
+/// use small_iter::IntoSmallIter;
+///
+/// #[derive(IntoSmallIter)]
+/// struct One(#[small_iter(into_iter)] Vec<u8>);
+/// ```
+#[proc_macro_derive(IntoSmallIter, attributes(small_iter))]
+pub fn derive_into_small_iter(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The options from the `#[small_iter(...)]` attributes on the struct.
+#[derive(Default)]
+struct Options {
+    field: Option<Member>,
+    into_iter: bool,
+}
+
+impl Options {
+    fn parse(input: &DeriveInput) -> Result<Self> {
+        let mut options = Options::default();
+        for attr in &input.attrs {
+            if !attr.path().is_ident("small_iter") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("field") {
+                    options.field = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("into_iter") {
+                    options.into_iter = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `field = ...` or `into_iter`"))
+                }
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// Returns the `#[small_iter(field)]` attribute on `field`, if any. Anything
+/// else in `#[small_iter(...)]` is an error, so that it isn't silently
+/// ignored.
+fn parse_field_marker(field: &Field) -> Result<Option<&Attribute>> {
+    let mut marker = None;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("small_iter") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            let has_value = meta.input.peek(Token![=]) || meta.input.peek(token::Paren);
+            if !meta.path.is_ident("field") || has_value {
+                return Err(meta.error("expected `field`"));
+            }
+            if marker.is_some() {
+                return Err(meta.error("duplicate `field`"));
+            }
+            marker = Some(attr);
+            Ok(())
+        })?;
+    }
+    Ok(marker)
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let options = Options::parse(&input)?;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            Span::call_site(),
+            "`IntoSmallIter` can only be derived for structs",
+        ));
+    };
+
+    let fields: Vec<(Member, &Type)> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            };
+            (member, &field.ty)
+        })
+        .collect();
+    let mut marked = None;
+    for (i, field) in data.fields.iter().enumerate() {
+        if let Some(attr) = parse_field_marker(field)? {
+            if options.field.is_some() || marked.is_some() {
+                return Err(Error::new_spanned(attr, "only one field can be selected"));
+            }
+            marked = Some(i);
+        }
+    }
+    let selected = match (&options.field, marked) {
+        (_, Some(i)) => i,
+        (Some(member), None) => fields
+            .iter()
+            .position(|(other, _)| other == member)
+            .ok_or_else(|| Error::new(member.span(), "no field with this name"))?,
+        (None, None) if fields.len() == 1 => 0,
+        (None, None) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "`IntoSmallIter` requires exactly one field, or a field selected with \
+                 `#[small_iter(field)]`",
+            ))
+        }
+    };
+    let (member, ty) = &fields[selected];
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(#ty: ::small_iter::IntoSmallIterExt));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Bindings for destructuring and rebuilding `self` in
+    // `try_into_small_iter`.
+    let members = fields.iter().map(|(member, _)| member);
+    let bindings: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("__field_{}", i))
+        .collect();
+    let selected_binding = &bindings[selected];
+    let pattern = quote!(Self { #(#members: #bindings),* });
+
+    let ext = quote!(<#ty as ::small_iter::IntoSmallIterExt>);
+    let mut output = quote! {
+        impl #impl_generics ::small_iter::__private::Sealed for #name #ty_generics #where_clause {}
+
+        impl #impl_generics ::small_iter::IntoSmallIterExt for #name #ty_generics #where_clause {
+            type Item = #ext::Item;
+            type Alloc = #ext::Alloc;
+
+            fn into_small_iter(self) -> ::small_iter::SmallIter<Self::Item, Self::Alloc> {
+                #ext::into_small_iter(self.#member)
+            }
+
+            fn into_small_iter_keep_capacity(
+                self,
+            ) -> ::small_iter::SmallIter<Self::Item, Self::Alloc> {
+                #ext::into_small_iter_keep_capacity(self.#member)
+            }
+
+            fn try_into_small_iter(
+                self,
+            ) -> ::core::result::Result<::small_iter::SmallIter<Self::Item, Self::Alloc>, Self> {
+                let #pattern = self;
+                #ext::try_into_small_iter(#selected_binding)
+                    .map_err(|#selected_binding| #pattern)
+            }
+        }
+    };
+    if options.into_iter {
+        output.extend(quote! {
+            impl #impl_generics ::core::iter::IntoIterator for #name #ty_generics #where_clause {
+                type Item = #ext::Item;
+                type IntoIter = ::small_iter::SmallIter<#ext::Item, #ext::Alloc>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    ::small_iter::IntoSmallIterExt::into_small_iter(self)
+                }
+            }
+        });
+    }
+    Ok(output)
+}
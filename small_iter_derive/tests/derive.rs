@@ -0,0 +1,65 @@
+use small_iter::{IntoSmallIter, IntoSmallIterExt, SmallIter};
+
+#[derive(IntoSmallIter)]
+struct Wrapper(Vec<u32>);
+
+#[derive(IntoSmallIter)]
+#[small_iter(field = 1, into_iter)]
+struct Tuple(&'static str, Box<[u32]>);
+
+#[derive(Debug, IntoSmallIter)]
+#[small_iter(field = items)]
+struct Named<T> {
+    name: String,
+    items: Vec<T>,
+}
+
+#[derive(IntoSmallIter)]
+struct Marked {
+    #[small_iter(field)]
+    bytes: Vec<u8>,
+    other: Vec<u8>,
+}
+
+#[test]
+fn newtype() {
+    let iter = Wrapper(vec![1, 2, 3]).into_small_iter();
+    assert_eq!(iter.as_slice(), &[1, 2, 3]);
+}
+
+#[test]
+fn into_iter() {
+    let iter: SmallIter<u32> = Tuple("a", Box::new([1, 2])).into_iter();
+    assert_eq!(iter.as_slice(), &[1, 2]);
+    let mut sum = 0;
+    for x in Tuple("b", Box::new([3, 4])) {
+        sum += x;
+    }
+    assert_eq!(sum, 7);
+}
+
+#[test]
+fn generic_named() {
+    // Too little spare capacity to keep it without reallocating.
+    let mut items = Vec::with_capacity(3);
+    items.push(b'x');
+    let named = Named {
+        name: String::from("a"),
+        items,
+    };
+    let named = named.try_into_small_iter().unwrap_err();
+    assert_eq!(named.name, "a");
+    assert_eq!(named.items, b"x");
+    let iter = named.into_small_iter_keep_capacity();
+    assert_eq!(iter.as_slice(), b"x");
+}
+
+#[test]
+fn field_marker() {
+    let marked = Marked {
+        bytes: vec![1, 2],
+        other: vec![3],
+    };
+    assert_eq!(marked.other, [3]);
+    assert_eq!(marked.into_small_iter().as_slice(), &[1, 2]);
+}
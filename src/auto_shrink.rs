@@ -0,0 +1,171 @@
+use alloc::fmt::{self, Debug};
+use core::iter::FusedIterator;
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::SmallIter;
+
+/// An iterator that wraps a [`SmallIter`] and releases the memory of the
+/// consumed elements once enough of them have been consumed.
+///
+/// This struct is created by [`SmallIter::auto_shrink`].
+///
+/// Whenever at least `threshold` of the allocation no longer holds remaining
+/// elements, this calls [`SmallIter::shrink_consumed`], which moves the
+/// remaining elements to the start of the allocation and shrinks it. This
+/// counts both the consumed elements and any excess capacity kept by
+/// [`into_small_iter_keep_capacity`](crate::IntoSmallIterExt::into_small_iter_keep_capacity).
+/// Lower thresholds release memory sooner, but move the elements more often.
+///
+/// ```
+/// use small_iter::IntoSmallIterExt;
+///
+/// let mut iter = vec![1, 2, 3, 4].into_small_iter().auto_shrink(0.5);
+/// assert_eq!(iter.next(), Some(1));
+/// assert_eq!(iter.inner().original_len(), 4);
+/// assert_eq!(iter.next(), Some(2));
+/// assert_eq!(iter.inner().original_len(), 2);
+/// assert_eq!(iter.inner().as_slice(), &[3, 4]);
+/// ```
+pub struct AutoShrink<T, A: Allocator = Global> {
+    iter: SmallIter<T, A>,
+    threshold: f64,
+    /// The number of remaining elements at or below which to shrink the
+    /// allocation, or `None` if there is no memory to release.
+    shrink_at: Option<usize>,
+}
+
+impl<T, A: Allocator> AutoShrink<T, A> {
+    pub(crate) fn new(iter: SmallIter<T, A>, threshold: f64) -> Self {
+        assert!(
+            threshold > 0.0 && threshold <= 1.0,
+            "the threshold must be in the range `0.0 < threshold <= 1.0`"
+        );
+        let mut this = AutoShrink {
+            iter,
+            threshold,
+            shrink_at: None,
+        };
+        this.update_shrink_at();
+        this
+    }
+
+    /// Returns the threshold, as a fraction of the allocation.
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// Returns a reference to the underlying `SmallIter`.
+    pub fn inner(&self) -> &SmallIter<T, A> {
+        &self.iter
+    }
+
+    /// Returns the underlying `SmallIter`.
+    pub fn into_inner(self) -> SmallIter<T, A> {
+        self.iter
+    }
+
+    /// Computes the largest number of remaining elements that leaves at
+    /// least `threshold` of the current allocation unused.
+    fn update_shrink_at(&mut self) {
+        let (_, capacity) = self.iter.allocation();
+        if capacity == 0 {
+            // ZSTs and empty allocations have no memory to release.
+            self.shrink_at = None;
+            return;
+        }
+        let limit = self.threshold * capacity as f64;
+        let mut unused = limit as usize;
+        if (unused as f64) < limit {
+            unused += 1;
+        }
+        self.shrink_at = Some(capacity - unused.min(capacity));
+    }
+}
+
+impl<T, A: Allocator> Iterator for AutoShrink<T, A> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = self.iter.next()?;
+        if self.shrink_at.is_some_and(|n| self.iter.len() <= n) {
+            self.iter.shrink_consumed();
+            self.update_shrink_at();
+        }
+        Some(element)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.iter.count()
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for AutoShrink<T, A> {}
+
+impl<T, A: Allocator> FusedIterator for AutoShrink<T, A> {}
+
+impl<T: Debug, A: Allocator> Debug for AutoShrink<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoShrink")
+            .field("iter", &self.iter)
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for AutoShrink<T, A> {
+    fn clone(&self) -> Self {
+        self.iter.clone().auto_shrink(self.threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IntoSmallIterExt, SmallIter};
+    use alloc::{boxed::Box, vec, vec::Vec};
+
+    #[test]
+    fn auto_shrink() {
+        let mut iter = (0..10)
+            .map(Box::new)
+            .collect::<SmallIter<_>>()
+            .auto_shrink(0.3);
+        for i in 0..10 {
+            assert_eq!(iter.next(), Some(Box::new(i)));
+            let (_, capacity) = iter.inner().allocation();
+            assert!(iter.inner().consumed() * 10 < capacity * 3 || capacity == 0);
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.into_inner().allocation().1, 0);
+    }
+
+    #[test]
+    fn auto_shrink_keep_capacity() {
+        let mut v = Vec::with_capacity(100);
+        v.extend((0..10).map(Box::new));
+        let mut iter = v.into_small_iter_keep_capacity().auto_shrink(0.5);
+        // Most of the allocation is excess capacity, so the first element
+        // already triggers a shrink.
+        assert_eq!(iter.next(), Some(Box::new(0)));
+        assert_eq!(iter.inner().allocation().1, 9);
+        for i in 1..9 {
+            assert_eq!(iter.next(), Some(Box::new(i)));
+        }
+        let iter = iter.into_inner();
+        assert_eq!(iter.len(), 1);
+        assert!(iter.into_vec().capacity() < 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn auto_shrink_zero_threshold() {
+        let _ = vec![1, 2, 3].into_small_iter().auto_shrink(0.0);
+    }
+}
@@ -0,0 +1,114 @@
+use alloc::fmt::{self, Debug};
+use core::iter::FusedIterator;
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::SmallIter;
+
+/// An iterator that yields the elements of a [`SmallIter`] in arrays of `N`
+/// elements.
+///
+/// This struct is created by [`SmallIter::array_chunks`].
+///
+/// If the number of elements is not a multiple of `N`, the last few elements
+/// are not yielded. Use [`into_remainder`](Self::into_remainder) to get them.
+pub struct ArrayChunks<T, const N: usize, A: Allocator = Global> {
+    iter: SmallIter<T, A>,
+}
+
+impl<T, const N: usize, A: Allocator> ArrayChunks<T, N, A> {
+    pub(crate) fn new(iter: SmallIter<T, A>) -> Self {
+        const { assert!(N != 0, "chunk size must be non-zero") };
+        ArrayChunks { iter }
+    }
+
+    /// Returns the elements at the end that will not be yielded, since there
+    /// are fewer than `N` of them.
+    pub fn remainder(&self) -> &[T] {
+        let slice = self.iter.as_slice();
+        &slice[slice.len() - slice.len() % N..]
+    }
+
+    /// Returns the remaining elements as a `SmallIter`.
+    ///
+    /// Once this iterator has returned `None`, these are the elements in
+    /// [`remainder`](Self::remainder).
+    pub fn into_remainder(self) -> SmallIter<T, A> {
+        self.iter
+    }
+}
+
+impl<T, const N: usize, A: Allocator> Iterator for ArrayChunks<T, N, A> {
+    type Item = [T; N];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next_chunk().ok()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.iter.len() / N;
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.iter.len() / N
+    }
+}
+
+impl<T, const N: usize, A: Allocator> ExactSizeIterator for ArrayChunks<T, N, A> {}
+
+impl<T, const N: usize, A: Allocator> FusedIterator for ArrayChunks<T, N, A> {}
+
+impl<T: Debug, const N: usize, A: Allocator> Debug for ArrayChunks<T, N, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArrayChunks")
+            .field("iter", &self.iter)
+            .finish()
+    }
+}
+
+impl<T: Clone, const N: usize, A: Allocator + Clone> Clone for ArrayChunks<T, N, A> {
+    fn clone(&self) -> Self {
+        ArrayChunks {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IntoSmallIterExt, SmallIter};
+    use alloc::{boxed::Box, vec};
+
+    #[test]
+    fn array_chunks() {
+        let mut chunks = (1..=5)
+            .map(Box::new)
+            .collect::<SmallIter<_>>()
+            .array_chunks::<2>();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.remainder(), &[Box::new(5)]);
+        assert_eq!(chunks.next(), Some([Box::new(1), Box::new(2)]));
+        let remainder = chunks.clone().into_remainder();
+        assert_eq!(
+            remainder.as_slice(),
+            &[Box::new(3), Box::new(4), Box::new(5)]
+        );
+        assert_eq!(chunks.next(), Some([Box::new(3), Box::new(4)]));
+        assert_eq!(chunks.next(), None);
+        assert_eq!(chunks.into_remainder().as_slice(), &[Box::new(5)]);
+    }
+
+    #[test]
+    fn array_chunks_zst() {
+        let mut chunks = vec![(); 7].into_small_iter().array_chunks::<3>();
+        assert_eq!(chunks.next(), Some([(); 3]));
+        assert_eq!(chunks.next(), Some([(); 3]));
+        assert_eq!(chunks.next(), None);
+        let remainder = chunks.into_remainder();
+        assert_eq!((remainder.consumed(), remainder.len()), (6, 1));
+    }
+}
@@ -0,0 +1,144 @@
+use alloc::fmt::{self, Debug};
+use core::{iter::FusedIterator, marker::PhantomData, mem::size_of, ptr, slice};
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::SmallIter;
+
+/// An iterator that moves out a number of elements from the front of a
+/// [`SmallIter`].
+///
+/// This struct is created by [`SmallIter::drain_front`]. Any elements that
+/// are not yielded are dropped when this iterator is dropped.
+pub struct DrainFront<'a, T, A: Allocator = Global> {
+    /*
+    - SAFETY invariant: the elements in `iter` are owned by this iterator.
+      The `SmallIter` has already been advanced past them, so it won't read
+      or drop them.
+    - `start` points to the first element of the original `iter`. Dropping
+      the elements must go through it, since `iter` is only derived from a
+      shared reference.
+     */
+    iter: slice::Iter<'a, T>,
+    start: *mut T,
+    _phantom: PhantomData<&'a mut SmallIter<T, A>>,
+}
+
+impl<'a, T, A: Allocator> DrainFront<'a, T, A> {
+    /// # Safety
+    ///
+    /// The `len` elements at `start` must be initialized, valid for `'a`,
+    /// and owned by the new iterator.
+    pub(crate) unsafe fn new(start: *mut T, len: usize) -> Self {
+        DrainFront {
+            // SAFETY: The elements are initialized and valid for `'a`.
+            iter: unsafe { slice::from_raw_parts(start, len) }.iter(),
+            start,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the elements that haven't been yielded yet as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        self.iter.as_slice()
+    }
+}
+
+// `slice::Iter<'a, T>` is only `Send` if `T: Sync`, but we move the elements
+// out, so this is like `&mut SmallIter<T, A>` instead.
+unsafe impl<T: Send, A: Allocator + Send> Send for DrainFront<'_, T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for DrainFront<'_, T, A> {}
+
+impl<T, A: Allocator> Iterator for DrainFront<'_, T, A> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY: The element is owned by this iterator, and `iter` won't
+        // return it again.
+        self.iter
+            .next()
+            .map(|element| unsafe { ptr::read(element) })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T, A: Allocator> DoubleEndedIterator for DrainFront<'_, T, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        // SAFETY: The element is owned by this iterator, and `iter` won't
+        // return it again.
+        self.iter
+            .next_back()
+            .map(|element| unsafe { ptr::read(element) })
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for DrainFront<'_, T, A> {}
+
+impl<T, A: Allocator> FusedIterator for DrainFront<'_, T, A> {}
+
+impl<T: Debug, A: Allocator> Debug for DrainFront<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DrainFront").field(&self.as_slice()).finish()
+    }
+}
+
+impl<T, A: Allocator> Drop for DrainFront<'_, T, A> {
+    fn drop(&mut self) {
+        let remaining = self.iter.as_slice();
+        // SAFETY: We drop only the elements that haven't been yielded, which
+        // are owned by this iterator. They are in the same allocation as
+        // `start`.
+        unsafe {
+            let remaining_start = if const { size_of::<T>() == 0 } {
+                self.start
+            } else {
+                self.start
+                    .add(remaining.as_ptr().offset_from(self.start) as usize)
+            };
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                remaining_start,
+                remaining.len(),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IntoSmallIterExt, SmallIter};
+    use alloc::{boxed::Box, vec};
+
+    #[test]
+    fn drain_front() {
+        let mut iter = (1..=5).map(Box::new).collect::<SmallIter<_>>();
+        let mut drain = iter.drain_front(3);
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next_back(), Some(Box::new(3)));
+        assert_eq!(drain.next(), Some(Box::new(1)));
+        drop(drain);
+        assert_eq!(iter.consumed(), 3);
+        assert_eq!(iter.as_slice(), &[Box::new(4), Box::new(5)]);
+        assert_eq!(iter.drain_front(2).count(), 2);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn drain_front_zst() {
+        let mut iter = vec![(); 3].into_small_iter();
+        assert_eq!(iter.drain_front(2).count(), 2);
+        assert_eq!((iter.consumed(), iter.len()), (2, 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_front_too_many() {
+        let mut iter = vec![1, 2, 3].into_small_iter();
+        let _ = iter.drain_front(4);
+    }
+}
@@ -0,0 +1,67 @@
+use alloc::fmt::{self, Debug};
+use core::iter::FusedIterator;
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::SmallIter;
+
+/// An iterator that yields the remaining elements of a [`SmallIter`] along
+/// with their indices in the original sequence of elements.
+///
+/// This struct is created by [`SmallIter::enumerate_absolute`].
+///
+/// The index is computed from the position of the element in the allocation,
+/// so this is the same size as the `SmallIter`. For the same reason, this
+/// doesn't implement `Clone`: a clone of the `SmallIter` has a new
+/// allocation, so it would start counting from zero.
+pub struct EnumerateAbsolute<T, A: Allocator = Global> {
+    iter: SmallIter<T, A>,
+}
+
+impl<T, A: Allocator> EnumerateAbsolute<T, A> {
+    pub(crate) fn new(iter: SmallIter<T, A>) -> Self {
+        EnumerateAbsolute { iter }
+    }
+
+    /// Returns a reference to the underlying `SmallIter`.
+    pub fn inner(&self) -> &SmallIter<T, A> {
+        &self.iter
+    }
+
+    /// Returns the underlying `SmallIter`.
+    pub fn into_inner(self) -> SmallIter<T, A> {
+        self.iter
+    }
+}
+
+impl<T, A: Allocator> Iterator for EnumerateAbsolute<T, A> {
+    type Item = (usize, T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.iter.position();
+        self.iter.next().map(|element| (index, element))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.iter.count()
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for EnumerateAbsolute<T, A> {}
+
+impl<T, A: Allocator> FusedIterator for EnumerateAbsolute<T, A> {}
+
+impl<T: Debug, A: Allocator> Debug for EnumerateAbsolute<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnumerateAbsolute")
+            .field("iter", &self.iter)
+            .finish()
+    }
+}
@@ -0,0 +1,227 @@
+//! Implementations of `IntoSmallIterExt` for other standard library types.
+//! Each one picks the cheapest way to get a `Box<[T]>` or `Vec<T>` out of
+//! `self`.
+
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    collections::{BinaryHeap, VecDeque},
+    rc::Rc,
+    string::String,
+    sync::Arc,
+    vec::Vec,
+};
+use core::{mem::ManuallyDrop, ptr};
+
+use allocator_api2::alloc::Global;
+
+use crate::{IntoSmallIterExt, Sealed, SmallIter};
+
+impl<T, const N: usize> Sealed for [T; N] {}
+
+/// This moves the elements into a new allocation of exactly `N` elements.
+impl<T, const N: usize> IntoSmallIterExt for [T; N] {
+    type Item = T;
+    type Alloc = Global;
+
+    fn into_small_iter(self) -> SmallIter<T> {
+        (Box::new(self) as Box<[T]>).into_small_iter()
+    }
+}
+
+impl<T: Clone> Sealed for Cow<'_, [T]> {}
+
+/// If the slice is borrowed, this clones the elements into a new allocation
+/// of exactly the right size. If it's owned, this is the same as for
+/// `Vec<T>`.
+impl<T: Clone> IntoSmallIterExt for Cow<'_, [T]> {
+    type Item = T;
+    type Alloc = Global;
+
+    fn into_small_iter(self) -> SmallIter<T> {
+        match self {
+            Cow::Borrowed(slice) => Box::<[T]>::from(slice).into_small_iter(),
+            Cow::Owned(vec) => vec.into_small_iter(),
+        }
+    }
+
+    fn into_small_iter_keep_capacity(self) -> SmallIter<T> {
+        match self {
+            Cow::Borrowed(slice) => Box::<[T]>::from(slice).into_small_iter(),
+            Cow::Owned(vec) => vec.into_small_iter_keep_capacity(),
+        }
+    }
+
+    fn try_into_small_iter(self) -> Result<SmallIter<T>, Self> {
+        match self {
+            Cow::Borrowed(slice) => Ok(Box::<[T]>::from(slice).into_small_iter()),
+            Cow::Owned(vec) => vec.try_into_small_iter().map_err(Cow::Owned),
+        }
+    }
+}
+
+/// Moves the elements out of a uniquely owned shared slice into a new
+/// allocation, and frees the shared slice.
+macro_rules! into_small_iter_shared {
+    ($shared:ident, $slice:expr) => {{
+        let mut slice = $slice;
+        if $shared::get_mut(&mut slice).is_none() {
+            return Box::<[T]>::from(&*slice).into_small_iter();
+        }
+        let len = slice.len();
+        let mut vec = Vec::with_capacity(len);
+        // SAFETY: `slice` is uniquely owned, so we can move the elements out
+        // of it. `ManuallyDrop<T>` has the same layout as `T`, so we can
+        // free the shared slice without dropping the elements again.
+        unsafe {
+            let slice = $shared::from_raw($shared::into_raw(slice) as *const [ManuallyDrop<T>]);
+            ptr::copy_nonoverlapping(slice.as_ptr().cast::<T>(), vec.as_mut_ptr(), len);
+            vec.set_len(len);
+        }
+        vec.into_small_iter()
+    }};
+}
+
+impl<T: Clone> Sealed for Rc<[T]> {}
+
+/// This always allocates a new allocation of exactly the right size. If the
+/// `Rc` is uniquely owned (with no `Weak` pointers either), the elements are
+/// moved into it. Otherwise, they are cloned.
+impl<T: Clone> IntoSmallIterExt for Rc<[T]> {
+    type Item = T;
+    type Alloc = Global;
+
+    fn into_small_iter(self) -> SmallIter<T> {
+        into_small_iter_shared!(Rc, self)
+    }
+}
+
+impl<T: Clone> Sealed for Arc<[T]> {}
+
+/// This always allocates a new allocation of exactly the right size. If the
+/// `Arc` is uniquely owned (with no `Weak` pointers either), the elements are
+/// moved into it. Otherwise, they are cloned.
+impl<T: Clone> IntoSmallIterExt for Arc<[T]> {
+    type Item = T;
+    type Alloc = Global;
+
+    fn into_small_iter(self) -> SmallIter<T> {
+        into_small_iter_shared!(Arc, self)
+    }
+}
+
+impl<T> Sealed for VecDeque<T> {}
+
+/// This converts the deque into a `Vec<T>`, which may move the elements
+/// within the allocation, but doesn't reallocate. Then, this is the same as
+/// for `Vec<T>`.
+impl<T> IntoSmallIterExt for VecDeque<T> {
+    type Item = T;
+    type Alloc = Global;
+
+    fn into_small_iter(self) -> SmallIter<T> {
+        Vec::from(self).into_small_iter()
+    }
+
+    fn into_small_iter_keep_capacity(self) -> SmallIter<T> {
+        Vec::from(self).into_small_iter_keep_capacity()
+    }
+
+    fn try_into_small_iter(self) -> Result<SmallIter<T>, Self> {
+        Vec::from(self)
+            .try_into_small_iter()
+            .map_err(VecDeque::from)
+    }
+}
+
+impl<T> Sealed for BinaryHeap<T> {}
+
+/// The elements are yielded in an arbitrary order, like
+/// [`BinaryHeap::into_vec`]. Then, this is the same as for `Vec<T>`.
+///
+/// If [`try_into_small_iter`](IntoSmallIterExt::try_into_small_iter) fails,
+/// the returned heap is rebuilt from the elements, which takes `O(n)` time.
+impl<T: Ord> IntoSmallIterExt for BinaryHeap<T> {
+    type Item = T;
+    type Alloc = Global;
+
+    fn into_small_iter(self) -> SmallIter<T> {
+        self.into_vec().into_small_iter()
+    }
+
+    fn into_small_iter_keep_capacity(self) -> SmallIter<T> {
+        self.into_vec().into_small_iter_keep_capacity()
+    }
+
+    fn try_into_small_iter(self) -> Result<SmallIter<T>, Self> {
+        self.into_vec()
+            .try_into_small_iter()
+            .map_err(BinaryHeap::from)
+    }
+}
+
+impl Sealed for String {}
+
+/// This iterates over the bytes of the string. This is the same as for
+/// `Vec<u8>`.
+impl IntoSmallIterExt for String {
+    type Item = u8;
+    type Alloc = Global;
+
+    fn into_small_iter(self) -> SmallIter<u8> {
+        self.into_bytes().into_small_iter()
+    }
+
+    fn into_small_iter_keep_capacity(self) -> SmallIter<u8> {
+        self.into_bytes().into_small_iter_keep_capacity()
+    }
+
+    fn try_into_small_iter(self) -> Result<SmallIter<u8>, Self> {
+        self.into_bytes().try_into_small_iter().map_err(|bytes| {
+            // SAFETY: The bytes are unchanged, so they're still valid UTF-8.
+            unsafe { String::from_utf8_unchecked(bytes) }
+        })
+    }
+}
+
+impl Sealed for Box<str> {}
+
+/// This iterates over the bytes of the string. This is cheap, like for
+/// `Box<[u8]>`.
+impl IntoSmallIterExt for Box<str> {
+    type Item = u8;
+    type Alloc = Global;
+
+    fn into_small_iter(self) -> SmallIter<u8> {
+        self.into_boxed_bytes().into_small_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn other_sources() {
+        assert_eq!([1, 2].into_small_iter().as_slice(), &[1, 2]);
+        let v = vec![Box::new(1)];
+        assert_eq!(Cow::from(&v[..]).into_small_iter().as_slice(), &v[..]);
+        assert_eq!(Cow::from(v.clone()).into_small_iter().as_slice(), &v[..]);
+        let rc: Rc<[Box<i32>]> = Rc::from(v.clone());
+        let rc2 = rc.clone();
+        assert_eq!(rc.into_small_iter().as_slice(), &v[..]);
+        assert_eq!(rc2.into_small_iter().as_slice(), &v[..]);
+        let arc: Arc<[Box<i32>]> = Arc::from(v.clone());
+        assert_eq!(arc.into_small_iter().as_slice(), &v[..]);
+        let mut deque = VecDeque::from(vec![2, 3]);
+        deque.push_front(1);
+        assert_eq!(deque.into_small_iter().as_slice(), &[1, 2, 3]);
+        let mut heap: Vec<_> = BinaryHeap::from(vec![2, 3, 1]).into_small_iter().collect();
+        heap.sort();
+        assert_eq!(heap, [1, 2, 3]);
+        assert_eq!(String::from("ab").into_small_iter().as_slice(), b"ab");
+        let s: Box<str> = "ab".into();
+        assert_eq!(s.into_small_iter().as_slice(), b"ab");
+    }
+}
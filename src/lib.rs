@@ -1,80 +1,300 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
+#![cfg_attr(
+    feature = "nightly",
+    feature(
+        allocator_api,
+        dropck_eyepatch,
+        min_specialization,
+        rustc_attrs,
+        trusted_len,
+        trusted_random_access
+    )
+)]
+#![cfg_attr(feature = "nightly", allow(internal_features))]
 
 extern crate alloc;
 use alloc::{
     boxed::Box,
+    collections::VecDeque,
     fmt::{self, Debug},
-    vec::Vec,
+    vec::{self, Vec},
 };
 use core::{
     iter::FusedIterator,
     marker::PhantomData,
-    mem::{size_of, ManuallyDrop},
+    mem::{self, align_of, size_of, ManuallyDrop, MaybeUninit},
+    num::NonZeroUsize,
     ptr::{self, NonNull},
     slice,
 };
 
-trait Sealed {}
+use allocator_api2::alloc::{Allocator, Global};
+
+/// The `allocator-api2` crate, which provides the allocator types used by
+/// this crate on stable Rust. Use this re-export to name them, so that the
+/// versions always match.
+pub use allocator_api2;
+
+mod auto_shrink;
+mod chunks;
+mod drain;
+mod enumerate;
+mod impls;
+mod map;
+#[cfg(feature = "nightly")]
+mod nightly;
+mod parts;
+mod retain;
+mod small32;
+mod smaller;
+mod thin;
+#[cfg(feature = "thin-vec")]
+mod thin_vec_impls;
+
+pub use auto_shrink::AutoShrink;
+pub use chunks::ArrayChunks;
+pub use drain::DrainFront;
+pub use enumerate::EnumerateAbsolute;
+pub use parts::IntoBoxedSliceParts;
+pub use small32::SmallIter32;
+pub use smaller::SmallerIter;
+pub use thin::ThinSmallIter;
+
+#[cfg(feature = "derive")]
+pub use small_iter_derive::IntoSmallIter;
+
+/// Not public API. Used by `#[derive(IntoSmallIter)]`, which has to name
+/// `Sealed` from outside this crate. Implementing anything from here by hand
+/// is unsupported, and may break in any release.
+#[doc(hidden)]
+pub mod __private {
+    pub trait Sealed {}
+}
+
+use __private::Sealed;
 
 /// An extension trait that provides the `into_small_iter` method on `Vec<T>`
 /// and `Box<[T]>`.
 ///
 /// Note that for `Vec<T>`, if there is excess capacity in the vector, calling
 /// `into_small_iter` will first shrink the allocation to fit the existing
-/// elements. Depending on the allocator, this may reallocate.
+/// elements. Depending on the allocator, this may reallocate. To avoid this,
+/// use [`into_small_iter_keep_capacity`](Self::into_small_iter_keep_capacity)
+/// or [`try_into_small_iter`](Self::try_into_small_iter).
 ///
 /// On the other hand, calling `into_small_iter` on a `Box<[T]>` is cheap.
-#[allow(private_bounds)]
+///
+/// This trait is also implemented for arrays, `Cow<[T]>`, `Rc<[T]>`,
+/// `Arc<[T]>`, `VecDeque<T>`, `BinaryHeap<T>`, `String`, and `Box<str>`. See
+/// the documentation of each impl for when it allocates.
+///
+/// This trait is not meant to be implemented directly, and doing so is
+/// unsupported. Instead, other containers can implement
+/// [`IntoBoxedSliceParts`], which provides an implementation of this trait.
+/// With the `derive` feature, wrappers around a container can use
+/// `#[derive(IntoSmallIter)]` to forward to it.
+///
+/// This trait is also implemented for `Box<[T], A>` and `Vec<T, A>` with a
+/// custom allocator `A`. On stable Rust, these are the types from the
+/// [`allocator_api2`] crate. With the `nightly` feature enabled, these are
+/// the standard library types, using the unstable `allocator_api` feature.
 pub trait IntoSmallIterExt: Sealed {
     /// The type of the elements.
     type Item;
 
+    /// The allocator that the elements are allocated with.
+    type Alloc: Allocator;
+
     /// Consumes `self` and returns an [`SmallIter`] that moves out of it.
-    fn into_small_iter(self) -> SmallIter<Self::Item>;
+    fn into_small_iter(self) -> SmallIter<Self::Item, Self::Alloc>;
+
+    /// Consumes `self` and returns an [`SmallIter`] that moves out of it,
+    /// keeping any excess capacity if possible.
+    ///
+    /// For `Vec<T>`, the capacity is recorded in the unused capacity of the
+    /// vector, which avoids reallocating. This requires the unused capacity
+    /// to have room for two pointers, plus padding. If there isn't enough
+    /// room, this falls back to
+    /// [`into_small_iter`](Self::into_small_iter).
+    ///
+    /// Note that the excess capacity is kept alive until the iterator is
+    /// dropped.
+    fn into_small_iter_keep_capacity(self) -> SmallIter<Self::Item, Self::Alloc>
+    where
+        Self: Sized,
+    {
+        self.into_small_iter()
+    }
+
+    /// Consumes `self` and returns an [`SmallIter`] that moves out of it, or
+    /// returns `self` back if this would reallocate.
+    ///
+    /// This behaves like
+    /// [`into_small_iter_keep_capacity`](Self::into_small_iter_keep_capacity),
+    /// except that it returns `Err(self)` instead of falling back to
+    /// [`into_small_iter`](Self::into_small_iter).
+    fn try_into_small_iter(self) -> Result<SmallIter<Self::Item, Self::Alloc>, Self>
+    where
+        Self: Sized,
+    {
+        Ok(self.into_small_iter())
+    }
 }
 
-impl<T> Sealed for Box<[T]> {}
-impl<T> Sealed for Vec<T> {}
+impl<C: IntoBoxedSliceParts> Sealed for C {}
+impl<T, A: Allocator> Sealed for allocator_api2::vec::Vec<T, A> {}
 
-impl<T> IntoSmallIterExt for Box<[T]> {
-    type Item = T;
+/// This includes `Box<[T], A>`.
+impl<C: IntoBoxedSliceParts> IntoSmallIterExt for C {
+    type Item = C::Item;
+    type Alloc = C::Alloc;
 
-    fn into_small_iter(self) -> SmallIter<T> {
-        // SAFETY: the slice is owned by `self`, so it's safe to move out of it.
-        let slice_ptr: *mut [T] = Box::into_raw(self);
-        let (start, end) = if const { size_of::<T>() == 0 } {
-            let dangling = NonNull::<T>::dangling();
-            (
-                dangling,
-                dangling.as_ptr().wrapping_byte_add(slice_ptr.len()),
-            )
+    fn into_small_iter(self) -> SmallIter<C::Item, C::Alloc> {
+        // SAFETY: the slice is owned by us now, as guaranteed by the
+        // implementation of `IntoBoxedSliceParts`, so it's safe to move out
+        // of it.
+        let (first_element_ptr, len, alloc) = self.into_boxed_slice_parts();
+        let (start, end) = if const { size_of::<C::Item>() == 0 } {
+            let dangling = NonNull::<C::Item>::dangling();
+            (dangling, dangling.as_ptr().wrapping_byte_add(len))
         } else {
-            let first_element_ptr = slice_ptr.cast::<T>();
             // SAFETY: We set `start` and `end` to be the beginning and end of the slice.
             // The elements in between are initialized.
-            unsafe {
-                (
-                    NonNull::new_unchecked(first_element_ptr),
-                    first_element_ptr.add(slice_ptr.len()),
-                )
-            }
+            unsafe { (first_element_ptr, first_element_ptr.as_ptr().add(len)) }
         };
         SmallIter {
             elements_start: start,
             allocation_start: start,
             end,
+            alloc: ManuallyDrop::new(alloc),
             _phantom: PhantomData,
         }
     }
 }
 
+impl<T, A: Allocator> IntoSmallIterExt for allocator_api2::vec::Vec<T, A> {
+    type Item = T;
+    type Alloc = A;
+
+    fn into_small_iter(self) -> SmallIter<T, A> {
+        self.into_boxed_slice().into_small_iter()
+    }
+
+    fn into_small_iter_keep_capacity(self) -> SmallIter<T, A> {
+        match self.try_into_small_iter() {
+            Ok(iter) => iter,
+            Err(vec) => vec.into_small_iter(),
+        }
+    }
+
+    fn try_into_small_iter(self) -> Result<SmallIter<T, A>, Self> {
+        if const { size_of::<T>() == 0 } || self.len() == self.capacity() {
+            // This doesn't reallocate.
+            return Ok(self.into_small_iter());
+        }
+
+        let start = self.as_ptr();
+        // SAFETY: `start..end` is the initialized part of the vector.
+        let end = unsafe { start.add(self.len()) };
+        let allocation_end = start as usize + self.capacity() * size_of::<T>();
+        // The header must be strictly after `end`. See the comments in
+        // `SmallIter` for why.
+        let header_addr = (end as usize + 1).next_multiple_of(align_of::<CapacityHeader<T>>());
+        if header_addr + size_of::<CapacityHeader<T>>() > allocation_end {
+            return Err(self);
+        }
+
+        let mut vec = ManuallyDrop::new(self);
+        let start = vec.as_mut_ptr();
+        // SAFETY: We've checked that the header fits in the unused capacity.
+        // We use `start` to derive the pointers to preserve provenance. We
+        // take ownership of the allocator, and `vec` is never dropped.
+        unsafe {
+            let end = start.add(vec.len());
+            let header = start
+                .byte_add(header_addr - start as usize)
+                .cast::<CapacityHeader<T>>();
+            header.write(CapacityHeader {
+                allocation_start: NonNull::new_unchecked(start),
+                capacity: vec.capacity(),
+            });
+            Ok(SmallIter {
+                elements_start: NonNull::new_unchecked(start),
+                allocation_start: NonNull::new_unchecked(header.cast()),
+                end,
+                alloc: ManuallyDrop::new(ptr::read(vec.allocator())),
+                _phantom: PhantomData,
+            })
+        }
+    }
+}
+
+// With the `nightly` feature, these are the same types as the ones from
+// `allocator_api2`. Otherwise, we forward to the `allocator_api2` types,
+// which use the same global allocator.
+#[cfg(not(feature = "nightly"))]
+impl<T> Sealed for Box<[T]> {}
+#[cfg(not(feature = "nightly"))]
+impl<T> Sealed for Vec<T> {}
+
+#[cfg(not(feature = "nightly"))]
+impl<T> IntoSmallIterExt for Box<[T]> {
+    type Item = T;
+    type Alloc = Global;
+
+    fn into_small_iter(self) -> SmallIter<T> {
+        // SAFETY: Both `Box` types use the global allocator.
+        unsafe { allocator_api2::boxed::Box::from_raw(Box::into_raw(self)) }.into_small_iter()
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
 impl<T> IntoSmallIterExt for Vec<T> {
     type Item = T;
+    type Alloc = Global;
 
     fn into_small_iter(self) -> SmallIter<T> {
         self.into_boxed_slice().into_small_iter()
     }
+
+    fn into_small_iter_keep_capacity(self) -> SmallIter<T> {
+        to_allocator_api2_vec(self).into_small_iter_keep_capacity()
+    }
+
+    fn try_into_small_iter(self) -> Result<SmallIter<T>, Self> {
+        to_allocator_api2_vec(self)
+            .try_into_small_iter()
+            .map_err(from_allocator_api2_vec)
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+fn to_allocator_api2_vec<T>(vec: Vec<T>) -> allocator_api2::vec::Vec<T> {
+    let mut vec = ManuallyDrop::new(vec);
+    // SAFETY: Both `Vec` types use the global allocator, and `vec` is never
+    // dropped.
+    unsafe { allocator_api2::vec::Vec::from_raw_parts(vec.as_mut_ptr(), vec.len(), vec.capacity()) }
+}
+
+fn from_allocator_api2_vec<T>(vec: allocator_api2::vec::Vec<T>) -> Vec<T> {
+    #[cfg(feature = "nightly")]
+    {
+        vec
+    }
+    #[cfg(not(feature = "nightly"))]
+    {
+        let (ptr, len, capacity, Global) = vec.into_raw_parts_with_alloc();
+        // SAFETY: Both `Vec` types use the global allocator.
+        unsafe { Vec::from_raw_parts(ptr, len, capacity) }
+    }
+}
+
+/// The true location and capacity of an allocation that has excess capacity.
+/// This is stored in the unused capacity. See the comments in `SmallIter`.
+struct CapacityHeader<T> {
+    allocation_start: NonNull<T>,
+    capacity: usize,
 }
 
 /// A 3-pointer iterator that moves out of a `Vec<T>` or `Box<[T]>`
@@ -85,8 +305,11 @@ impl<T> IntoSmallIterExt for Vec<T> {
 /// this iterator is represented as 3 pointers.
 /// In exchange, it does not implement [`DoubleEndedIterator`].
 ///
+/// The allocator `A` is stored in the iterator, so the size is unchanged for
+/// zero-sized allocators such as the default [`Global`].
+///
 /// See the [crate-level documentation](crate) for more details.
-pub struct SmallIter<T> {
+pub struct SmallIter<T, A: Allocator = Global> {
     /*
     Similarly to how `std::vec::IntoIter` is implemented,
     we store things differently depending on whether
@@ -97,63 +320,828 @@ pub struct SmallIter<T> {
     - The remaining elements are at `elements_start..end`
     - SAFETY invariant: the memory from `elements_start` to `end` is initialized
 
+    Exception: If the allocation has excess capacity after `end` (see
+    `IntoSmallIterExt::into_small_iter_keep_capacity`), then:
+    - `allocation_start` points to a `CapacityHeader<T>` stored in the excess
+      capacity, strictly after `end`. We can tell this case apart by checking
+      `allocation_start > elements_start`.
+    - The header records the real start and capacity of the allocation.
+    Use `SmallIter::allocation` instead of accessing `allocation_start`
+    directly.
+
     If `T` is a ZST:
-    - `allocation_start == elements_start == dangling`
+    - `allocation_start == dangling`
     - `end` is n bytes after `dangling`, where n is the number of elements
+    - `elements_start` is m bytes after `dangling`, where m is the number of
+      consumed elements. This saturates instead of wrapping around to null,
+      so m might be less than the true count in extreme cases.
+    - Use `SmallIter::elements_ptr` to get a pointer usable for reading.
      */
     elements_start: NonNull<T>,
     allocation_start: NonNull<T>,
     end: *const T,
+    alloc: ManuallyDrop<A>,
     _phantom: PhantomData<T>,
 }
 
 impl<T> SmallIter<T> {
+    /// Creates an iterator of `len` elements, where each element is produced
+    /// by calling `f` with its index.
+    ///
+    /// The elements are written directly into an allocation of exactly `len`
+    /// elements, so this never reallocates.
+    pub fn from_fn<F: FnMut(usize) -> T>(len: usize, mut f: F) -> Self {
+        let mut builder = SmallIterBuilder::with_capacity(len);
+        for i in 0..len {
+            // The builder has room for exactly `len` elements.
+            let _ = builder.push(f(i));
+        }
+        builder.build()
+    }
+
+    /// Converts the remaining elements into a `Vec<T>`, reusing the allocation
+    /// of the iterator.
+    ///
+    /// The remaining elements are moved to the front of the allocation, so
+    /// this never allocates. If no elements have been consumed yet, no
+    /// elements are moved either.
+    ///
+    /// The returned vector's capacity is the original length of the iterator,
+    /// or the full capacity of the original vector if the iterator was
+    /// created with
+    /// [`into_small_iter_keep_capacity`](IntoSmallIterExt::into_small_iter_keep_capacity)
+    /// and kept it.
+    ///
+    /// For iterators with a custom allocator, use the `From` impl for
+    /// `Vec<T, A>` instead.
+    pub fn into_vec(self) -> Vec<T> {
+        from_allocator_api2_vec(self.into_allocator_api2_vec())
+    }
+
+    /// Converts the remaining elements into a `Box<[T]>`, reusing the
+    /// allocation of the iterator.
+    ///
+    /// If no elements have been consumed yet and the iterator has no excess
+    /// capacity, this is cheap. Otherwise, the allocation will be shrunk to
+    /// fit the remaining elements. Depending on the allocator, this may
+    /// reallocate. Excess capacity is only kept by
+    /// [`into_small_iter_keep_capacity`](IntoSmallIterExt::into_small_iter_keep_capacity).
+    ///
+    /// For iterators with a custom allocator, use the `From` impl for
+    /// `Box<[T], A>` instead.
+    pub fn into_boxed_slice(self) -> Box<[T]> {
+        self.into_vec().into_boxed_slice()
+    }
+
+    /// Converts the remaining elements into a [`vec::IntoIter`], which is
+    /// double-ended, reusing the allocation of the iterator.
+    ///
+    /// Like [`into_vec`](Self::into_vec), the remaining elements are moved to
+    /// the front of the allocation, so this never allocates.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3].into_small_iter();
+    /// assert_eq!(iter.next(), Some(1));
+    /// let mut iter = iter.into_std_iter();
+    /// assert_eq!(iter.next_back(), Some(3));
+    /// ```
+    pub fn into_std_iter(self) -> vec::IntoIter<T> {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<T, A: Allocator> SmallIter<T, A> {
     /// Returns the remaining elements in the iterator as a slice.
     pub fn as_slice(&self) -> &[T] {
-        unsafe { slice::from_raw_parts(self.elements_start.as_ptr(), self.elements_len()) }
+        unsafe { slice::from_raw_parts(self.elements_ptr(), self.elements_len()) }
     }
 
     /// Returns the remaining elements in the iterator as a mutable slice.
     pub fn as_mut_slice(&mut self) -> &mut [T] {
-        unsafe { slice::from_raw_parts_mut(self.elements_start.as_ptr(), self.elements_len()) }
+        unsafe { slice::from_raw_parts_mut(self.elements_ptr(), self.elements_len()) }
+    }
+
+    /// Returns a reference to the next element, without consuming it.
+    ///
+    /// Unlike wrapping the iterator in [`Peekable`](core::iter::Peekable),
+    /// this doesn't take up any extra space.
+    pub fn peek(&self) -> Option<&T> {
+        self.as_slice().first()
+    }
+
+    /// Returns a mutable reference to the next element, without consuming it.
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.as_mut_slice().first_mut()
+    }
+
+    /// Returns a reference to the `n`th remaining element (starting from 0),
+    /// without consuming anything.
+    pub fn peek_nth(&self, n: usize) -> Option<&T> {
+        self.as_slice().get(n)
+    }
+
+    /// Consumes and returns the next element if `func` returns `true` for it.
+    /// Otherwise, returns `None` without consuming anything.
+    pub fn next_if(&mut self, func: impl FnOnce(&T) -> bool) -> Option<T> {
+        if func(self.peek()?) {
+            self.next()
+        } else {
+            None
+        }
+    }
+
+    /// Consumes and returns the next element if it is equal to `expected`.
+    /// Otherwise, returns `None` without consuming anything.
+    pub fn next_if_eq<U>(&mut self, expected: &U) -> Option<T>
+    where
+        U: ?Sized,
+        T: PartialEq<U>,
+    {
+        self.next_if(|next| next == expected)
+    }
+
+    /// Pushes `value` back to the front of the iterator, so that it's
+    /// returned by the next call to `next`.
+    ///
+    /// This reuses the slot of an already consumed element, so this takes
+    /// O(1) time and never allocates. If no elements have been consumed yet
+    /// (or all consumed slots have been reused already), `value` is returned
+    /// back in an `Err`.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3].into_small_iter();
+    /// assert_eq!(iter.push_front(0), Err(0));
+    /// assert_eq!(iter.next(), Some(1));
+    /// assert_eq!(iter.push_front(10), Ok(()));
+    /// assert_eq!(iter.as_slice(), &[10, 2, 3]);
+    /// ```
+    pub fn push_front(&mut self, value: T) -> Result<(), T> {
+        if self.consumed_len() == 0 {
+            return Err(value);
+        }
+        if const { size_of::<T>() == 0 } {
+            // The value is conjured back in `next`.
+            mem::forget(value);
+            self.end = self.end.wrapping_byte_add(1);
+            // SAFETY: There's at least 1 consumed element, so this doesn't
+            // go below `allocation_start`.
+            self.elements_start = unsafe {
+                NonNull::new_unchecked(self.elements_start.as_ptr().wrapping_byte_sub(1))
+            };
+        } else {
+            // SAFETY: There's at least 1 consumed element, so the slot just
+            // before `elements_start` is in the allocation and unused.
+            unsafe {
+                let new_start = self.elements_start.as_ptr().sub(1);
+                new_start.write(value);
+                self.elements_start = NonNull::new_unchecked(new_start);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes clones of the elements of `slice` back to the front of the
+    /// iterator, so that the next calls to `next` return them in order.
+    ///
+    /// Like [`push_front`](Self::push_front), this reuses the slots of already
+    /// consumed elements. If there aren't enough such slots, this returns
+    /// `false` without pushing anything.
+    ///
+    /// If `clone` panics, only some of the elements will have been pushed.
+    pub fn push_front_slice(&mut self, slice: &[T]) -> bool
+    where
+        T: Clone,
+    {
+        if self.consumed_len() < slice.len() {
+            return false;
+        }
+        for element in slice.iter().rev() {
+            let result = self.push_front(element.clone());
+            debug_assert!(result.is_ok());
+        }
+        true
+    }
+
+    /// Releases the memory of the consumed elements, by moving the remaining
+    /// elements to the start of the allocation and shrinking it to fit them.
+    ///
+    /// This also releases any excess capacity kept by
+    /// [`into_small_iter_keep_capacity`](IntoSmallIterExt::into_small_iter_keep_capacity).
+    /// Depending on the allocator, shrinking may reallocate. Afterwards, no
+    /// elements are counted as [`consumed`](Self::consumed).
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3].into_small_iter();
+    /// assert_eq!(iter.next(), Some(1));
+    /// iter.shrink_consumed();
+    /// assert_eq!((iter.consumed(), iter.original_len()), (0, 2));
+    /// assert_eq!(iter.as_slice(), &[2, 3]);
+    /// ```
+    pub fn shrink_consumed(&mut self) {
+        if const { size_of::<T>() == 0 } {
+            // There's no memory to release, but reset the consumed count.
+            self.elements_start = self.allocation_start;
+            return;
+        }
+        let (allocation_start, capacity) = self.allocation();
+        let len = self.elements_len();
+        if len == capacity {
+            return;
+        }
+        let elements_start = self.elements_start;
+        // The vector below takes ownership of the allocation, so leave the
+        // iterator empty in case shrinking panics.
+        self.elements_start = NonNull::dangling();
+        self.allocation_start = NonNull::dangling();
+        self.end = NonNull::dangling().as_ptr();
+        // SAFETY: Both ranges are in the same allocation, and the remaining
+        // elements are initialized. `ptr::copy` handles the overlap. The
+        // allocation has the same layout as a `Vec<T, A>` with capacity
+        // `capacity`, and the first `len` elements are now initialized.
+        let mut vec = unsafe {
+            ptr::copy(elements_start.as_ptr(), allocation_start.as_ptr(), len);
+            allocator_api2::vec::Vec::from_raw_parts_in(
+                allocation_start.as_ptr(),
+                len,
+                capacity,
+                &*self.alloc,
+            )
+        };
+        vec.shrink_to_fit();
+        let start = ManuallyDrop::new(vec).as_mut_ptr();
+        // SAFETY: The allocation now has exactly `len` elements, which are
+        // initialized. We take back ownership of it from the vector.
+        unsafe {
+            self.elements_start = NonNull::new_unchecked(start);
+            self.allocation_start = self.elements_start;
+            self.end = start.add(len);
+        }
+    }
+
+    /// Wraps the iterator in an [`AutoShrink`], which calls
+    /// [`shrink_consumed`](Self::shrink_consumed) whenever at least
+    /// `threshold` of the allocation no longer holds remaining elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `0.0 < threshold <= 1.0`.
+    pub fn auto_shrink(self, threshold: f64) -> AutoShrink<T, A> {
+        AutoShrink::new(self, threshold)
+    }
+
+    /// Drops the next `n` elements, like the unstable
+    /// [`Iterator::advance_by`].
+    ///
+    /// The elements are dropped in one go, so this takes constant time if `T`
+    /// doesn't need to be dropped.
+    ///
+    /// If fewer than `n` elements remain, all of them are dropped, and this
+    /// returns `Err(k)`, where `k` is the number of elements that couldn't be
+    /// skipped.
+    ///
+    /// ```
+    /// use core::num::NonZeroUsize;
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3, 4].into_small_iter();
+    /// assert_eq!(iter.skip_n(2), Ok(()));
+    /// assert_eq!(iter.as_slice(), &[3, 4]);
+    /// assert_eq!(iter.skip_n(5), Err(NonZeroUsize::new(3).unwrap()));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn skip_n(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let steps = n.min(self.elements_len());
+        let to_drop = ptr::slice_from_raw_parts_mut(self.elements_ptr(), steps);
+        // SAFETY: There are at least `steps` elements. We advance past them
+        // before dropping them, so that they aren't dropped again if dropping
+        // panics.
+        unsafe {
+            self.advance_unchecked(steps);
+            ptr::drop_in_place(to_drop);
+        }
+        NonZeroUsize::new(n - steps).map_or(Ok(()), Err)
+    }
+
+    /// Drops the elements for which `pred` returns `true`, up to the first
+    /// element for which it returns `false`, and returns how many were
+    /// dropped.
+    ///
+    /// The remaining elements must be partitioned by `pred`, like in
+    /// [`slice::partition_point`]: all the elements for which `pred` returns
+    /// `true` must come before all the elements for which it returns `false`.
+    /// Otherwise, the result is unspecified.
+    ///
+    /// This uses a galloping (exponential) search, so it takes `O(log k)`
+    /// calls to `pred` to skip `k` elements, which is fast if the element is
+    /// near the front. The skipped elements are dropped as in
+    /// [`skip_n`](Self::skip_n).
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3, 5, 8, 13].into_small_iter();
+    /// assert_eq!(iter.advance_to_partition_point(|&x| x < 4), 3);
+    /// assert_eq!(iter.as_slice(), &[5, 8, 13]);
+    /// ```
+    pub fn advance_to_partition_point<P>(&mut self, mut pred: P) -> usize
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let slice = self.as_slice();
+        // Find a range `lower..upper` which contains the partition point, by
+        // doubling the distance from the front.
+        let mut lower = 0;
+        let mut upper = 1;
+        while upper <= slice.len() && pred(&slice[upper - 1]) {
+            lower = upper;
+            upper = upper.saturating_mul(2);
+        }
+        let upper = upper.min(slice.len());
+        let skipped = lower + slice[lower..upper].partition_point(pred);
+        // `skipped` is at most the number of elements.
+        let _ = self.skip_n(skipped);
+        skipped
+    }
+
+    /// Drops the elements that are less than `key`, and returns how many were
+    /// dropped.
+    ///
+    /// The remaining elements must be sorted. This is like
+    /// [`advance_to_partition_point`](Self::advance_to_partition_point) with
+    /// `|x| x < key`.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3, 5, 8, 13].into_small_iter();
+    /// assert_eq!(iter.seek(&5), 3);
+    /// assert_eq!(iter.next(), Some(5));
+    /// assert_eq!(iter.seek(&5), 0);
+    /// ```
+    pub fn seek(&mut self, key: &T) -> usize
+    where
+        T: Ord,
+    {
+        self.advance_to_partition_point(|element| element < key)
+    }
+
+    /// Drops the elements whose key is less than `key`, and returns how many
+    /// were dropped.
+    ///
+    /// The remaining elements must be sorted by the key extracted with `f`.
+    /// This is like [`advance_to_partition_point`](Self::advance_to_partition_point)
+    /// with `|x| f(x) < *key`.
+    pub fn seek_by_key<K, F>(&mut self, key: &K, mut f: F) -> usize
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.advance_to_partition_point(|element| f(element) < *key)
+    }
+
+    /// Creates an iterator that moves out the next `n` elements.
+    ///
+    /// The `SmallIter` is advanced past the `n` elements right away. If the
+    /// returned iterator is dropped before yielding all of them, the rest are
+    /// dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the number of remaining elements.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3, 4].into_small_iter();
+    /// let mut drain = iter.drain_front(3);
+    /// assert_eq!(drain.next(), Some(1));
+    /// assert_eq!(drain.as_slice(), &[2, 3]);
+    /// drop(drain);
+    /// assert_eq!(iter.as_slice(), &[4]);
+    /// ```
+    pub fn drain_front(&mut self, n: usize) -> DrainFront<'_, T, A> {
+        assert!(
+            n <= self.elements_len(),
+            "cannot drain {n} elements from an iterator with {} elements",
+            self.elements_len()
+        );
+        let start = self.elements_ptr();
+        // SAFETY: We've checked that there are at least `n` elements. Those
+        // elements stay initialized in the allocation, and are owned by the
+        // `DrainFront` from now on.
+        unsafe {
+            self.advance_unchecked(n);
+            DrainFront::new(start, n)
+        }
+    }
+
+    /// Moves the next `n` elements to the end of `vec`.
+    ///
+    /// The elements are copied in one go, instead of one at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the number of remaining elements.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3, 4].into_small_iter();
+    /// let mut v = vec![0];
+    /// iter.move_front_into(&mut v, 2);
+    /// assert_eq!(v, [0, 1, 2]);
+    /// assert_eq!(iter.as_slice(), &[3, 4]);
+    /// ```
+    pub fn move_front_into(&mut self, vec: &mut Vec<T>, n: usize) {
+        assert!(
+            n <= self.elements_len(),
+            "cannot move {n} elements from an iterator with {} elements",
+            self.elements_len()
+        );
+        vec.reserve(n);
+        // SAFETY: We've checked that there are at least `n` elements, and
+        // reserved room for them in `vec`. The elements are moved out of the
+        // iterator by advancing it.
+        unsafe {
+            ptr::copy_nonoverlapping(self.elements_ptr(), vec.as_mut_ptr().add(vec.len()), n);
+            vec.set_len(vec.len() + n);
+            self.advance_unchecked(n);
+        }
+    }
+
+    /// Moves the next `n` elements into a new `Box<[T]>`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the number of remaining elements.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3, 4].into_small_iter();
+    /// assert_eq!(*iter.split_off_front(3), [1, 2, 3]);
+    /// assert_eq!(iter.as_slice(), &[4]);
+    /// ```
+    pub fn split_off_front(&mut self, n: usize) -> Box<[T]> {
+        let mut vec = Vec::with_capacity(n);
+        self.move_front_into(&mut vec, n);
+        vec.into_boxed_slice()
+    }
+
+    /// Moves the next `N` elements out as an array.
+    ///
+    /// The elements are copied in one go, instead of one at a time. If fewer
+    /// than `N` elements remain, the iterator is left unchanged, and the
+    /// number of remaining elements is returned in an `Err`.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3, 4, 5].into_small_iter();
+    /// assert_eq!(iter.next_chunk(), Ok([1, 2]));
+    /// assert_eq!(iter.next_chunk::<4>(), Err(3));
+    /// assert_eq!(iter.as_slice(), &[3, 4, 5]);
+    /// ```
+    pub fn next_chunk<const N: usize>(&mut self) -> Result<[T; N], usize> {
+        let len = self.elements_len();
+        if len < N {
+            return Err(len);
+        }
+        let mut array = MaybeUninit::<[T; N]>::uninit();
+        // SAFETY: We've checked that there are at least `N` elements. They
+        // are moved into `array` by copying them and advancing the iterator.
+        unsafe {
+            ptr::copy_nonoverlapping(self.elements_ptr(), array.as_mut_ptr().cast::<T>(), N);
+            self.advance_unchecked(N);
+            Ok(array.assume_init())
+        }
+    }
+
+    /// Creates an iterator that yields the elements in arrays of `N`
+    /// elements.
+    ///
+    /// If the number of elements is not a multiple of `N`, the last few
+    /// elements are not yielded, and can be retrieved with
+    /// [`ArrayChunks::into_remainder`].
+    ///
+    /// `N` must not be 0. Otherwise, this is a compile-time error.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut chunks = vec![1, 2, 3, 4, 5].into_small_iter().array_chunks();
+    /// assert_eq!(chunks.next(), Some([1, 2]));
+    /// assert_eq!(chunks.next(), Some([3, 4]));
+    /// assert_eq!(chunks.next(), None);
+    /// assert_eq!(chunks.into_remainder().as_slice(), &[5]);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let chunks = vec![1, 2, 3].into_small_iter().array_chunks::<0>();
+    /// ```
+    pub fn array_chunks<const N: usize>(self) -> ArrayChunks<T, N, A> {
+        ArrayChunks::new(self)
+    }
+
+    /// Returns the number of elements that have been consumed.
+    ///
+    /// Elements pushed back with [`push_front`](Self::push_front) are no
+    /// longer counted as consumed.
+    ///
+    /// For ZSTs, this might be smaller than the true count if more than
+    /// `usize::MAX - align_of::<T>()` elements have been consumed.
+    pub fn consumed(&self) -> usize {
+        self.consumed_len()
+    }
+
+    /// Returns the number of elements that the iterator originally had.
+    ///
+    /// This is the sum of [`consumed`](Self::consumed) and
+    /// [`len`](ExactSizeIterator::len).
+    pub fn original_len(&self) -> usize {
+        self.consumed_len() + self.elements_len()
+    }
+
+    /// Returns the index of the next element in the original sequence of
+    /// elements.
+    ///
+    /// This is the same as [`consumed`](Self::consumed), since the remaining
+    /// elements are stored in their original positions. The exception is
+    /// after [`retain`](Self::retain) or [`dedup`](Self::dedup) and related
+    /// methods, which move the kept elements and count the removed ones as
+    /// consumed. Then, this is the index the next element would have if the
+    /// removed elements had been consumed instead.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec!['a', 'b', 'c'].into_small_iter();
+    /// assert_eq!(iter.next(), Some('a'));
+    /// assert_eq!(iter.position(), 1);
+    /// assert_eq!(iter.original_len(), 3);
+    /// ```
+    pub fn position(&self) -> usize {
+        self.consumed_len()
+    }
+
+    /// Creates an iterator that yields the remaining elements along with their
+    /// indices in the original sequence of elements.
+    ///
+    /// Unlike [`Iterator::enumerate`], this doesn't store a counter, so it's
+    /// the same size as the `SmallIter`. The indices come from
+    /// [`position`](Self::position), so after [`retain`](Self::retain) or
+    /// [`dedup`](Self::dedup), they are shifted by the number of removed
+    /// elements.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec!['a', 'b', 'c'].into_small_iter();
+    /// assert_eq!(iter.next(), Some('a'));
+    /// let v: Vec<_> = iter.enumerate_absolute().collect();
+    /// assert_eq!(v, [(1, 'b'), (2, 'c')]);
+    /// ```
+    pub fn enumerate_absolute(self) -> EnumerateAbsolute<T, A> {
+        EnumerateAbsolute::new(self)
+    }
+
+    /// Returns a reference to the underlying allocator.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Moves the remaining elements to the front of the allocation, and
+    /// returns them as a `Vec<T, A>` that owns the allocation.
+    fn into_allocator_api2_vec(self) -> allocator_api2::vec::Vec<T, A> {
+        let mut this = ManuallyDrop::new(self);
+        let len = this.elements_len();
+        // SAFETY: `this` is never dropped, so we take ownership of the
+        // allocator.
+        let alloc = unsafe { ManuallyDrop::take(&mut this.alloc) };
+        if const { size_of::<T>() == 0 } {
+            let mut vec = allocator_api2::vec::Vec::new_in(alloc);
+            // SAFETY: `T` is a ZST, so we can conjure them from thin air.
+            // Collecting ZSTs into a `Vec` doesn't allocate.
+            vec.extend((0..len).map(|_| unsafe { NonNull::<T>::dangling().as_ptr().read() }));
+            return vec;
+        }
+        let (allocation_start, capacity) = this.allocation();
+        if this.elements_start != allocation_start {
+            // SAFETY: Both ranges are in the same allocation, and the
+            // remaining elements are initialized. `ptr::copy` handles the
+            // overlap.
+            unsafe { ptr::copy(this.elements_start.as_ptr(), allocation_start.as_ptr(), len) };
+        }
+        // SAFETY: `allocation_start` was allocated by `alloc` with the layout
+        // of `capacity` elements of `T`, which is the same layout as a
+        // `Vec<T, A>` with that capacity. The first `len` elements are
+        // initialized, and `this` is never dropped, so ownership is
+        // transferred.
+        unsafe {
+            allocator_api2::vec::Vec::from_raw_parts_in(
+                allocation_start.as_ptr(),
+                len,
+                capacity,
+                alloc,
+            )
+        }
+    }
+
+    /// Advances the iterator by `n` elements, without dropping them.
+    ///
+    /// # Safety
+    ///
+    /// At least `n` elements must remain. The caller takes ownership of them.
+    unsafe fn advance_unchecked(&mut self, n: usize) {
+        if const { size_of::<T>() == 0 } {
+            self.end = self.end.wrapping_byte_sub(n);
+            // Like in `next`, the consumed count saturates instead of wrapping
+            // around to null.
+            let consumed = (self.elements_start.as_ptr() as usize).saturating_add(n);
+            // SAFETY: `consumed` is at least `elements_start`, so it's not null.
+            self.elements_start = unsafe {
+                NonNull::new_unchecked(
+                    self.elements_start
+                        .as_ptr()
+                        .wrapping_byte_add(consumed - self.elements_start.as_ptr() as usize),
+                )
+            };
+        } else {
+            // SAFETY: This doesn't go past `end`, as per the safety
+            // requirements.
+            self.elements_start = unsafe { self.elements_start.add(n) };
+        }
+    }
+
+    /// Returns a pointer to the first remaining element.
+    fn elements_ptr(&self) -> *mut T {
+        if const { size_of::<T>() == 0 } {
+            NonNull::dangling().as_ptr()
+        } else {
+            self.elements_start.as_ptr()
+        }
     }
 
     /// Returns the number of elements remaining in the iterator.
     fn elements_len(&self) -> usize {
         if const { size_of::<T>() == 0 } {
-            (self.end as usize).wrapping_sub(self.elements_start.as_ptr() as usize)
+            (self.end as usize).wrapping_sub(self.allocation_start.as_ptr() as usize)
         } else {
             // SAFETY: `elements_start..end` is from the same allocation.
             unsafe { self.end.offset_from(self.elements_start.as_ptr()) as usize }
         }
     }
 
-    /// Returns the number of elements in the allocation, including
-    /// uninitialized elements.
-    fn allocation_len(&self) -> usize {
+    /// Returns the number of consumed elements whose slots are before
+    /// `elements_start`.
+    fn consumed_len(&self) -> usize {
         if const { size_of::<T>() == 0 } {
-            0
+            self.elements_start.as_ptr() as usize - self.allocation_start.as_ptr() as usize
+        } else {
+            // SAFETY: `allocation_start..elements_start` is from the same
+            // allocation.
+            unsafe { self.elements_start.offset_from(self.allocation().0) as usize }
+        }
+    }
+
+    /// Returns the start of the allocation, and the number of elements in
+    /// the allocation, including uninitialized elements.
+    fn allocation(&self) -> (NonNull<T>, usize) {
+        if const { size_of::<T>() == 0 } {
+            (self.allocation_start, 0)
+        } else if self.allocation_start.as_ptr() > self.elements_start.as_ptr() {
+            // SAFETY: `allocation_start` points to a `CapacityHeader<T>`, as
+            // per the invariant.
+            let header = unsafe {
+                self.allocation_start
+                    .cast::<CapacityHeader<T>>()
+                    .as_ptr()
+                    .read()
+            };
+            (header.allocation_start, header.capacity)
         } else {
             // SAFETY: `allocation_start..end` is from the same allocation.
-            unsafe { self.end.offset_from(self.allocation_start.as_ptr()) as usize }
+            let len = unsafe { self.end.offset_from(self.allocation_start.as_ptr()) as usize };
+            (self.allocation_start, len)
         }
     }
 }
 
-unsafe impl<T: Send> Send for SmallIter<T> {}
-unsafe impl<T: Sync> Sync for SmallIter<T> {}
+/// Since `next` only copies the elements out of the allocation, the consumed
+/// elements are still valid if `T` is `Copy`. These methods allow looking at
+/// them and going back to them.
+impl<T: Copy, A: Allocator> SmallIter<T, A> {
+    /// Returns the elements that have already been consumed, as a slice.
+    ///
+    /// Elements pushed back with [`push_front`](Self::push_front) replace the
+    /// consumed elements in their slots.
+    ///
+    /// [`retain`](Self::retain), [`dedup`](Self::dedup) and related methods
+    /// add slots to the consumed part for the removed elements. These slots
+    /// hold copies of removed or kept elements in an unspecified order, so
+    /// this no longer returns the elements that were actually consumed.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = b"abc".to_vec().into_small_iter();
+    /// assert_eq!(iter.next(), Some(b'a'));
+    /// assert_eq!(iter.next(), Some(b'b'));
+    /// assert_eq!(iter.consumed_slice(), b"ab");
+    /// assert_eq!(iter.step_back(1), Ok(()));
+    /// assert_eq!(iter.as_slice(), b"bc");
+    /// iter.rewind();
+    /// assert_eq!(iter.as_slice(), b"abc");
+    /// ```
+    pub fn consumed_slice(&self) -> &[T] {
+        let start = if const { size_of::<T>() == 0 } {
+            NonNull::dangling()
+        } else {
+            self.allocation().0
+        };
+        // SAFETY: The consumed slots all hold valid elements, since they
+        // were only ever copied out or overwritten with other elements, and
+        // `T` is `Copy`.
+        unsafe { slice::from_raw_parts(start.as_ptr(), self.consumed_len()) }
+    }
 
-impl<T> Iterator for SmallIter<T> {
+    /// Moves the iterator back to the first element, so that all the
+    /// consumed elements are returned again.
+    ///
+    /// After [`retain`](Self::retain) or [`dedup`](Self::dedup), this returns
+    /// the contents of [`consumed_slice`](Self::consumed_slice) first, which
+    /// no longer match the original elements.
+    pub fn rewind(&mut self) {
+        // SAFETY: We step back by exactly the number of consumed elements.
+        unsafe { self.step_back_unchecked(self.consumed_len()) };
+    }
+
+    /// Moves the iterator back by `n` elements, so that the last `n` consumed
+    /// elements are returned again.
+    ///
+    /// If fewer than `n` elements have been consumed, this moves back to the
+    /// first element, and returns `Err(k)`, where `k` is the number of steps
+    /// that couldn't be taken.
+    pub fn step_back(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let consumed = self.consumed_len();
+        let steps = n.min(consumed);
+        // SAFETY: We step back by at most the number of consumed elements.
+        unsafe { self.step_back_unchecked(steps) };
+        NonZeroUsize::new(n - steps).map_or(Ok(()), Err)
+    }
+
+    /// Moves the iterator back by `n` elements.
+    ///
+    /// # Safety
+    ///
+    /// At least `n` elements must have been consumed.
+    unsafe fn step_back_unchecked(&mut self, n: usize) {
+        if const { size_of::<T>() == 0 } {
+            self.end = self.end.wrapping_byte_add(n);
+            // SAFETY: This doesn't go below `allocation_start`, as per the
+            // safety requirements.
+            self.elements_start = unsafe {
+                NonNull::new_unchecked(self.elements_start.as_ptr().wrapping_byte_sub(n))
+            };
+        } else {
+            // SAFETY: This doesn't go before the start of the allocation, as
+            // per the safety requirements.
+            self.elements_start = unsafe { self.elements_start.sub(n) };
+        }
+    }
+}
+
+unsafe impl<T: Send, A: Allocator + Send> Send for SmallIter<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for SmallIter<T, A> {}
+
+impl<T, A: Allocator> Iterator for SmallIter<T, A> {
     type Item = T;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        if ptr::eq(self.elements_start.as_ptr(), self.end) {
-            None
-        } else if const { size_of::<T>() == 0 } {
+        if const { size_of::<T>() == 0 } {
+            if ptr::eq(self.allocation_start.as_ptr(), self.end) {
+                return None;
+            }
             self.end = self.end.wrapping_byte_sub(1);
+            if self.elements_start.as_ptr() as usize != usize::MAX {
+                // SAFETY: We checked that this doesn't wrap around to null.
+                self.elements_start = unsafe {
+                    NonNull::new_unchecked(self.elements_start.as_ptr().wrapping_byte_add(1))
+                };
+            }
             // SAFETY: `T` is a ZST, so we can conjure one from thin air.
             Some(unsafe { NonNull::<T>::dangling().as_ptr().read() })
+        } else if ptr::eq(self.elements_start.as_ptr(), self.end) {
+            None
         } else {
             // SAFETY: the memory is initialized as per the invariant.
             let element = unsafe { self.elements_start.as_ptr().read() };
@@ -175,13 +1163,76 @@ impl<T> Iterator for SmallIter<T> {
     fn count(self) -> usize {
         self.elements_len()
     }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.skip_n(n).ok()?;
+        self.next()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        let len = self.elements_len();
+        self.nth(len.checked_sub(1)?)
+    }
+
+    // `min`, `max`, and the like are implemented in terms of `fold`. We can't
+    // override `try_fold`, since the `Try` trait is unstable.
+    #[inline]
+    fn fold<B, F>(mut self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        let mut acc = init;
+        if const { size_of::<T>() == 0 } {
+            for element in self.by_ref() {
+                acc = f(acc, element);
+            }
+        } else {
+            let end = self.end;
+            while !ptr::eq(self.elements_start.as_ptr(), end) {
+                // SAFETY: the memory is initialized as per the invariant, and
+                // we've checked that we're not at the end. We advance before
+                // calling `f`, so that the element isn't dropped again if `f`
+                // panics.
+                let element = unsafe {
+                    let element = self.elements_start.as_ptr().read();
+                    self.elements_start = self.elements_start.add(1);
+                    element
+                };
+                acc = f(acc, element);
+            }
+        }
+        acc
+    }
+
+    #[inline]
+    fn for_each<F>(self, mut f: F)
+    where
+        F: FnMut(Self::Item),
+    {
+        self.fold((), |(), element| f(element));
+    }
+
+    #[cfg(feature = "nightly")]
+    #[inline]
+    unsafe fn __iterator_get_unchecked(&mut self, i: usize) -> Self::Item
+    where
+        Self: core::iter::TrustedRandomAccessNoCoerce,
+    {
+        // SAFETY: The caller guarantees that `i` is in bounds, and that each
+        // index is accessed at most once. `TrustedRandomAccessNoCoerce` is
+        // only implemented for `T: Copy`, so reading the element without
+        // advancing doesn't cause a double drop.
+        unsafe { self.elements_ptr().add(i).read() }
+    }
 }
 
-impl<T> ExactSizeIterator for SmallIter<T> {}
+impl<T, A: Allocator> ExactSizeIterator for SmallIter<T, A> {}
 
-impl<T> FusedIterator for SmallIter<T> {}
+impl<T, A: Allocator> FusedIterator for SmallIter<T, A> {}
 
-impl<T: Debug> Debug for SmallIter<T> {
+impl<T: Debug, A: Allocator> Debug for SmallIter<T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple("IntoSmallIter")
             .field(&self.as_slice())
@@ -189,44 +1240,172 @@ impl<T: Debug> Debug for SmallIter<T> {
     }
 }
 
-impl<T> AsRef<[T]> for SmallIter<T> {
+impl<T, A: Allocator> AsRef<[T]> for SmallIter<T, A> {
     fn as_ref(&self) -> &[T] {
         self.as_slice()
     }
 }
 
-impl<T> AsMut<[T]> for SmallIter<T> {
+impl<T, A: Allocator> AsMut<[T]> for SmallIter<T, A> {
     fn as_mut(&mut self) -> &mut [T] {
         self.as_mut_slice()
     }
 }
 
-impl<T> Default for SmallIter<T> {
+impl<T, A: Allocator + Default> Default for SmallIter<T, A> {
     fn default() -> Self {
-        <Box<[T]>>::default().into_small_iter()
+        allocator_api2::vec::Vec::new_in(A::default()).into_small_iter()
     }
 }
 
-impl<T: Clone> Clone for SmallIter<T> {
+/// Clones the remaining elements into a new allocation of exactly the right
+/// size.
+///
+/// The consumed elements are not part of the clone, so for the clone,
+/// [`consumed`](SmallIter::consumed) and [`position`](SmallIter::position)
+/// start at zero, and [`original_len`](SmallIter::original_len) is the number
+/// of remaining elements.
+///
+/// ```
+/// use small_iter::IntoSmallIterExt;
+///
+/// let mut iter = vec![1, 2, 3].into_small_iter();
+/// iter.next();
+/// let clone = iter.clone();
+/// assert_eq!(clone.as_slice(), iter.as_slice());
+/// assert_eq!((iter.position(), iter.original_len()), (1, 3));
+/// assert_eq!((clone.position(), clone.original_len()), (0, 2));
+/// ```
+impl<T: Clone, A: Allocator + Clone> Clone for SmallIter<T, A> {
     fn clone(&self) -> Self {
-        <Box<[T]>>::from(self.as_slice()).into_small_iter()
+        let slice = self.as_slice();
+        // This allocates exactly `slice.len()` elements, so turning it into a
+        // `SmallIter` doesn't reallocate.
+        let mut vec =
+            allocator_api2::vec::Vec::with_capacity_in(slice.len(), A::clone(&self.alloc));
+        vec.extend_from_slice(slice);
+        vec.into_small_iter()
     }
 }
 
-impl<T> Drop for SmallIter<T> {
-    fn drop(&mut self) {
-        struct DropGuard<'a, T>(&'a mut SmallIter<T>);
+/// Collects the elements into a [`SmallIter`].
+///
+/// If the iterator reports an exact size via [`Iterator::size_hint`], the
+/// elements are written directly into an allocation of exactly that size, so
+/// this doesn't reallocate. Otherwise, the elements are first collected into
+/// a `Vec<T>`, which may reallocate as described in [`IntoSmallIterExt`].
+impl<T> FromIterator<T> for SmallIter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut iter = iter.into_iter();
+        match iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => {
+                let mut builder = SmallIterBuilder::with_capacity(lower);
+                for element in iter.by_ref().take(lower) {
+                    // The builder has room for exactly `lower` elements.
+                    let _ = builder.push(element);
+                }
+                // The size hint might be wrong, so handle any leftover elements.
+                let mut vec = builder.vec;
+                vec.extend(iter);
+                vec.into_small_iter()
+            }
+            _ => iter.collect::<Vec<T>>().into_small_iter(),
+        }
+    }
+}
+
+/// See [`SmallIter::into_vec`].
+impl<T, A: Allocator> From<SmallIter<T, A>> for allocator_api2::vec::Vec<T, A> {
+    fn from(iter: SmallIter<T, A>) -> Self {
+        iter.into_allocator_api2_vec()
+    }
+}
+
+/// See [`SmallIter::into_boxed_slice`].
+///
+/// With the `nightly` feature, this impl is not available for custom
+/// allocators due to the orphan rules. Convert to a `Vec<T, A>` first instead.
+#[cfg(not(feature = "nightly"))]
+impl<T, A: Allocator> From<SmallIter<T, A>> for allocator_api2::boxed::Box<[T], A> {
+    fn from(iter: SmallIter<T, A>) -> Self {
+        iter.into_allocator_api2_vec().into_boxed_slice()
+    }
+}
+
+/// See [`SmallIter::into_vec`].
+#[cfg(not(feature = "nightly"))]
+impl<T> From<SmallIter<T>> for Vec<T> {
+    fn from(iter: SmallIter<T>) -> Self {
+        iter.into_vec()
+    }
+}
+
+/// See [`SmallIter::into_boxed_slice`].
+impl<T> From<SmallIter<T>> for Box<[T]> {
+    fn from(iter: SmallIter<T>) -> Self {
+        iter.into_boxed_slice()
+    }
+}
+
+/// Reuses the allocation of the iterator, as in [`SmallIter::into_vec`].
+///
+/// `VecDeque` can't adopt an allocation with an offset, so the remaining
+/// elements are moved to the front of the allocation instead of leaving the
+/// consumed slots in front of them.
+impl<T> From<SmallIter<T>> for VecDeque<T> {
+    fn from(iter: SmallIter<T>) -> Self {
+        iter.into_vec().into()
+    }
+}
+
+/// See [`SmallIter::into_std_iter`].
+impl<T> From<SmallIter<T>> for vec::IntoIter<T> {
+    fn from(iter: SmallIter<T>) -> Self {
+        iter.into_std_iter()
+    }
+}
+
+/// Collects the remaining elements into a `Vec<T>`, which reuses the
+/// allocation of `iter` unless most of its elements have been consumed. The
+/// excess capacity is then handled as in
+/// [`IntoSmallIterExt::into_small_iter_keep_capacity`].
+impl<T> From<vec::IntoIter<T>> for SmallIter<T> {
+    fn from(iter: vec::IntoIter<T>) -> Self {
+        iter.collect::<Vec<T>>().into_small_iter_keep_capacity()
+    }
+}
 
-        impl<T> Drop for DropGuard<'_, T> {
+/// Moves the elements to be contiguous within the allocation of `deque`, and
+/// reuses it. The excess capacity is handled as in
+/// [`IntoSmallIterExt::into_small_iter_keep_capacity`].
+impl<T> From<VecDeque<T>> for SmallIter<T> {
+    fn from(deque: VecDeque<T>) -> Self {
+        Vec::from(deque).into_small_iter_keep_capacity()
+    }
+}
+
+impl<T, A: Allocator> SmallIter<T, A> {
+    /// Drops the remaining elements and frees the allocation.
+    ///
+    /// # Safety
+    ///
+    /// This must only be called once, from `Drop`.
+    unsafe fn drop_impl(&mut self) {
+        struct DropGuard<'a, T, A: Allocator>(&'a mut SmallIter<T, A>);
+
+        impl<T, A: Allocator> Drop for DropGuard<'_, T, A> {
             // Drop the Box allocation, but not the contained elements in the slice.
             fn drop(&mut self) {
-                let slice_ptr: *mut [ManuallyDrop<T>] = ptr::slice_from_raw_parts_mut(
-                    self.0.allocation_start.as_ptr().cast(),
-                    self.0.allocation_len(),
-                );
-                // SAFETY: We reconstruct the original `Box<[T]>`, but as a
-                // `Box<[ManuallyDrop<T>]>`, and then drop it.
-                unsafe { drop(Box::from_raw(slice_ptr)) };
+                let (allocation_start, allocation_len) = self.0.allocation();
+                let slice_ptr: *mut [ManuallyDrop<T>] =
+                    ptr::slice_from_raw_parts_mut(allocation_start.as_ptr().cast(), allocation_len);
+                // SAFETY: We reconstruct the original `Box<[T], A>`, but as a
+                // `Box<[ManuallyDrop<T>], A>`, and then drop it. The iterator
+                // is being dropped, so we can take the allocator.
+                unsafe {
+                    let alloc = ManuallyDrop::take(&mut self.0.alloc);
+                    drop(allocator_api2::boxed::Box::from_raw_in(slice_ptr, alloc));
+                }
             }
         }
 
@@ -234,7 +1413,7 @@ impl<T> Drop for SmallIter<T> {
         // SAFETY: We drop only the initialized elements.
         unsafe {
             ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
-                guard.0.elements_start.as_ptr(),
+                guard.0.elements_ptr(),
                 guard.0.elements_len(),
             ));
         }
@@ -242,9 +1421,118 @@ impl<T> Drop for SmallIter<T> {
     }
 }
 
+#[cfg(not(feature = "nightly"))]
+impl<T, A: Allocator> Drop for SmallIter<T, A> {
+    fn drop(&mut self) {
+        // SAFETY: This is called once, when the iterator is dropped.
+        unsafe { self.drop_impl() }
+    }
+}
+
+// SAFETY: Like `vec::IntoIter`, we don't access the elements while dropping
+// them, other than dropping them, which `PhantomData<T>` tells the drop checker
+// about. So the elements may contain dangling references.
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T, A: Allocator> Drop for SmallIter<T, A> {
+    fn drop(&mut self) {
+        // SAFETY: This is called once, when the iterator is dropped.
+        unsafe { self.drop_impl() }
+    }
+}
+
+/// A builder that writes elements directly into an exactly-sized allocation,
+/// and then turns it into a [`SmallIter`].
+///
+/// Unlike collecting into a `Vec<T>` and then calling
+/// [`into_small_iter`](IntoSmallIterExt::into_small_iter), this never
+/// reallocates as long as the builder is filled to its capacity.
+///
+/// ```
+/// use small_iter::SmallIterBuilder;
+///
+/// let mut builder = SmallIterBuilder::with_capacity(2);
+/// assert_eq!(builder.push(1), Ok(()));
+/// assert_eq!(builder.push(2), Ok(()));
+/// assert_eq!(builder.push(3), Err(3));
+/// let iter = builder.build();
+/// assert_eq!(iter.as_slice(), &[1, 2]);
+/// ```
+pub struct SmallIterBuilder<T> {
+    /*
+    - If `T` is not a ZST, then `vec.capacity() == capacity`
+    - `vec.len() <= capacity`
+     */
+    vec: Vec<T>,
+    capacity: usize,
+}
+
+impl<T> SmallIterBuilder<T> {
+    /// Creates a builder with room for exactly `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        // `Vec::with_capacity` is guaranteed to allocate exactly `capacity`
+        // elements.
+        SmallIterBuilder {
+            vec: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the number of elements pushed so far.
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Returns `true` if no elements have been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Returns the number of elements that the builder has room for.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns `true` if the builder is filled to its capacity.
+    pub fn is_full(&self) -> bool {
+        self.vec.len() == self.capacity
+    }
+
+    /// Appends an element.
+    ///
+    /// If the builder is already full, the element is returned back in an
+    /// `Err`.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            Err(value)
+        } else {
+            self.vec.push(value);
+            Ok(())
+        }
+    }
+
+    /// Turns the pushed elements into a [`SmallIter`].
+    ///
+    /// If the builder is full, this is cheap. Otherwise, the allocation will
+    /// be shrunk to fit the pushed elements. Depending on the allocator, this
+    /// may reallocate.
+    pub fn build(self) -> SmallIter<T> {
+        self.vec.into_small_iter()
+    }
+}
+
+impl<T: Debug> Debug for SmallIterBuilder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmallIterBuilder")
+            .field("elements", &self.vec.as_slice())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
 
     #[test]
     fn basic_exhaust() {
@@ -309,4 +1597,392 @@ mod tests {
         assert_eq!(iter.next(), Some(()));
         // Drop the iterator here
     }
+
+    #[test]
+    fn into_vec_unconsumed() {
+        let s: Box<[Box<i32>]> = Box::new([Box::new(1), Box::new(2), Box::new(3)]);
+        let allocation = s.as_ptr();
+        let iter = s.into_small_iter();
+        let v = iter.into_vec();
+        assert_eq!(v, [Box::new(1), Box::new(2), Box::new(3)]);
+        assert_eq!(v.as_ptr(), allocation);
+        assert_eq!(v.capacity(), 3);
+    }
+
+    #[test]
+    fn into_vec_partial() {
+        let s: Box<[Box<i32>]> = Box::new([Box::new(1), Box::new(2), Box::new(3)]);
+        let allocation = s.as_ptr();
+        let mut iter = s.into_small_iter();
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        let v = Vec::from(iter);
+        assert_eq!(v, [Box::new(2), Box::new(3)]);
+        assert_eq!(v.as_ptr(), allocation);
+        assert_eq!(v.capacity(), 3);
+    }
+
+    #[test]
+    fn into_boxed_slice_partial() {
+        let s: Box<[Box<i32>]> = Box::new([Box::new(1), Box::new(2), Box::new(3)]);
+        let mut iter = s.into_small_iter();
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        let b = <Box<[_]>>::from(iter);
+        assert_eq!(&*b, &[Box::new(2), Box::new(3)]);
+    }
+
+    #[test]
+    fn into_vec_zst() {
+        let s: Box<[()]> = Box::new([(); 3]);
+        let mut iter = s.into_small_iter();
+        assert_eq!(iter.next(), Some(()));
+        assert_eq!(iter.into_vec(), [(), ()]);
+        let iter = <Box<[()]>>::from([(); 3]).into_small_iter();
+        assert_eq!(&*iter.into_boxed_slice(), &[(); 3]);
+    }
+
+    #[test]
+    fn from_fn() {
+        let mut iter = SmallIter::from_fn(3, Box::new);
+        assert_eq!(iter.next(), Some(Box::new(0)));
+        assert_eq!(iter.as_slice(), &[Box::new(1), Box::new(2)]);
+    }
+
+    #[test]
+    fn collect_exact() {
+        let iter: SmallIter<i32> = (1..4).collect();
+        assert_eq!(iter.as_slice(), &[1, 2, 3]);
+        assert_eq!(iter.into_vec().capacity(), 3);
+    }
+
+    #[test]
+    fn collect_inexact() {
+        let iter: SmallIter<i32> = (1..10).filter(|x| x % 3 == 0).collect();
+        assert_eq!(iter.as_slice(), &[3, 6, 9]);
+        let iter: SmallIter<()> = (0..3).map(|_| ()).collect();
+        assert_eq!(iter.count(), 3);
+    }
+
+    #[test]
+    fn builder_partial() {
+        let mut builder = SmallIterBuilder::with_capacity(3);
+        assert_eq!(builder.push(Box::new(1)), Ok(()));
+        assert!(!builder.is_full());
+        let iter = builder.build();
+        assert_eq!(iter.as_slice(), &[Box::new(1)]);
+    }
+
+    #[test]
+    fn keep_capacity() {
+        let mut v: Vec<Box<i32>> = Vec::with_capacity(10);
+        v.extend([Box::new(1), Box::new(2), Box::new(3)]);
+        let allocation = v.as_ptr();
+        let mut iter = v.try_into_small_iter().unwrap();
+        assert_eq!(iter.as_slice(), &[Box::new(1), Box::new(2), Box::new(3)]);
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        let clone = iter.clone();
+        let v = iter.into_vec();
+        assert_eq!(v, [Box::new(2), Box::new(3)]);
+        assert_eq!(v.as_ptr(), allocation);
+        assert_eq!(v.capacity(), 10);
+
+        let mut iter = clone.into_vec().into_small_iter_keep_capacity();
+        assert_eq!(iter.next(), Some(Box::new(2)));
+        // Drop the iterator here
+    }
+
+    #[test]
+    fn keep_capacity_too_small() {
+        let mut v: Vec<u8> = Vec::with_capacity(4);
+        v.extend([1, 2, 3]);
+        let v = v.try_into_small_iter().unwrap_err();
+        assert_eq!(v.capacity(), 4);
+        let iter = v.into_small_iter_keep_capacity();
+        assert_eq!(iter.into_vec().capacity(), 3);
+    }
+
+    #[test]
+    fn size() {
+        assert_eq!(size_of::<SmallIter<u8>>(), 3 * size_of::<usize>());
+    }
+
+    #[test]
+    fn custom_allocator() {
+        use allocator_api2::alloc::{AllocError, Layout};
+        use core::cell::Cell;
+
+        #[derive(Default)]
+        struct Counting {
+            allocated: Cell<usize>,
+            deallocated: Cell<usize>,
+        }
+
+        unsafe impl Allocator for &Counting {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.allocated.set(self.allocated.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.deallocated.set(self.deallocated.get() + 1);
+                unsafe { Global.deallocate(ptr, layout) }
+            }
+        }
+
+        let counting = Counting::default();
+        let mut v = allocator_api2::vec::Vec::with_capacity_in(10, &counting);
+        v.extend([Box::new(1), Box::new(2), Box::new(3)]);
+        let mut iter = v.try_into_small_iter().unwrap();
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        let mut clone = iter.clone();
+        assert_eq!(clone.next(), Some(Box::new(2)));
+        let v = allocator_api2::vec::Vec::from(clone);
+        assert_eq!(v.as_slice(), &[Box::new(3)]);
+        drop(v);
+        drop(iter);
+        assert_eq!(counting.allocated.get(), 2);
+        assert_eq!(counting.deallocated.get(), 2);
+    }
+
+    #[test]
+    fn peek() {
+        let mut iter = vec![1, 2, 3].into_small_iter();
+        assert_eq!(iter.peek(), Some(&1));
+        assert_eq!(iter.peek_nth(2), Some(&3));
+        assert_eq!(iter.peek_nth(3), None);
+        *iter.peek_mut().unwrap() = 10;
+        assert_eq!(iter.next_if(|&x| x < 10), None);
+        assert_eq!(iter.next_if_eq(&10), Some(10));
+        assert_eq!(iter.next_if(|&x| x < 10), Some(2));
+        assert_eq!(iter.next_if_eq(&2), None);
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.peek(), None);
+        assert_eq!(iter.next_if(|_| true), None);
+    }
+
+    #[test]
+    fn push_front() {
+        let mut iter = vec![Box::new(1), Box::new(2), Box::new(3)].into_small_iter();
+        assert_eq!(iter.push_front(Box::new(0)), Err(Box::new(0)));
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        assert_eq!(iter.next(), Some(Box::new(2)));
+        assert!(!iter.push_front_slice(&[Box::new(4), Box::new(5), Box::new(6)]));
+        assert!(iter.push_front_slice(&[Box::new(4), Box::new(5)]));
+        assert_eq!(iter.push_front(Box::new(0)), Err(Box::new(0)));
+        assert_eq!(iter.as_slice(), &[Box::new(4), Box::new(5), Box::new(3)]);
+        assert_eq!(iter.next(), Some(Box::new(4)));
+        // Drop the iterator here
+    }
+
+    #[test]
+    fn push_front_zst() {
+        let mut iter = vec![(); 3].into_small_iter();
+        assert_eq!(iter.push_front(()), Err(()));
+        assert_eq!(iter.next(), Some(()));
+        assert_eq!(iter.next(), Some(()));
+        assert_eq!(iter.push_front(()), Ok(()));
+        assert!(iter.push_front_slice(&[()]));
+        assert!(!iter.push_front_slice(&[()]));
+        assert_eq!(iter.count(), 3);
+    }
+
+    #[test]
+    fn step_back() {
+        let mut iter = vec![1, 2, 3].into_small_iter();
+        assert_eq!(iter.consumed_slice(), &[]);
+        assert_eq!(iter.step_back(1), Err(NonZeroUsize::new(1).unwrap()));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.push_front(20), Ok(()));
+        assert_eq!(iter.consumed_slice(), &[1]);
+        assert_eq!(iter.step_back(3), Err(NonZeroUsize::new(2).unwrap()));
+        assert_eq!(iter.as_slice(), &[1, 20, 3]);
+        assert_eq!(iter.by_ref().count(), 3);
+    }
+
+    #[test]
+    fn rewind_zst() {
+        let mut iter = vec![(); 3].into_small_iter();
+        assert_eq!(iter.next(), Some(()));
+        assert_eq!(iter.next(), Some(()));
+        assert_eq!(iter.consumed_slice(), &[(), ()]);
+        assert_eq!(iter.step_back(1), Ok(()));
+        assert_eq!(iter.len(), 2);
+        iter.rewind();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.consumed_slice(), &[]);
+    }
+
+    #[test]
+    fn position() {
+        let mut iter = vec![1, 2, 3, 4].into_small_iter();
+        assert_eq!((iter.consumed(), iter.original_len()), (0, 4));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!((iter.consumed(), iter.position()), (2, 2));
+        assert_eq!(iter.push_front(20), Ok(()));
+        assert_eq!((iter.consumed(), iter.original_len()), (1, 4));
+        let mut iter = iter.enumerate_absolute();
+        assert_eq!(size_of_val(&iter), 3 * size_of::<usize>());
+        assert_eq!(iter.next(), Some((1, 20)));
+        assert_eq!(
+            iter.into_inner().enumerate_absolute().collect::<Vec<_>>(),
+            [(2, 3), (3, 4)]
+        );
+    }
+
+    #[test]
+    fn position_zst() {
+        let mut iter = vec![(); 3].into_small_iter();
+        assert_eq!(iter.next(), Some(()));
+        assert_eq!((iter.consumed(), iter.original_len()), (1, 3));
+        let v: Vec<_> = iter.enumerate_absolute().collect();
+        assert_eq!(v, [(1, ()), (2, ())]);
+    }
+
+    #[test]
+    fn shrink_consumed() {
+        let mut iter = vec![Box::new(1), Box::new(2), Box::new(3)].into_small_iter();
+        iter.shrink_consumed();
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        iter.shrink_consumed();
+        assert_eq!(iter.allocation().1, 2);
+        assert_eq!(iter.consumed(), 0);
+        assert_eq!(iter.as_slice(), &[Box::new(2), Box::new(3)]);
+        assert_eq!(iter.push_front(Box::new(1)), Err(Box::new(1)));
+        assert_eq!(iter.by_ref().count(), 2);
+        iter.shrink_consumed();
+        assert_eq!(iter.allocation().1, 0);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn shrink_consumed_keep_capacity() {
+        let mut v = Vec::with_capacity(100);
+        v.extend([Box::new(1), Box::new(2), Box::new(3)]);
+        let mut iter = v.into_small_iter_keep_capacity();
+        assert_eq!(iter.allocation().1, 100);
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        iter.shrink_consumed();
+        assert_eq!(iter.allocation().1, 2);
+        assert_eq!(iter.collect::<Vec<_>>(), [Box::new(2), Box::new(3)]);
+    }
+
+    #[test]
+    fn shrink_consumed_zst() {
+        let mut iter = vec![(); 3].into_small_iter();
+        assert_eq!(iter.next(), Some(()));
+        iter.shrink_consumed();
+        assert_eq!((iter.consumed(), iter.len()), (0, 2));
+    }
+
+    #[test]
+    fn split_off_front() {
+        let mut iter = (1..=5).map(Box::new).collect::<SmallIter<_>>();
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        let front = iter.split_off_front(2);
+        assert_eq!(*front, [Box::new(2), Box::new(3)]);
+        let mut v = Vec::new();
+        iter.move_front_into(&mut v, 0);
+        assert!(v.is_empty());
+        iter.move_front_into(&mut v, 2);
+        assert_eq!(v, [Box::new(4), Box::new(5)]);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn next_chunk() {
+        let mut iter = (1..=5).map(Box::new).collect::<SmallIter<_>>();
+        assert_eq!(iter.next_chunk::<0>(), Ok([]));
+        assert_eq!(iter.next_chunk(), Ok([Box::new(1), Box::new(2)]));
+        assert_eq!(iter.next_chunk::<4>(), Err(3));
+        assert_eq!(iter.consumed(), 2);
+        assert_eq!(
+            iter.next_chunk(),
+            Ok([Box::new(3), Box::new(4), Box::new(5)])
+        );
+        assert_eq!(iter.next_chunk::<1>(), Err(0));
+    }
+
+    #[test]
+    fn skip_n() {
+        let mut iter = (1..=5).map(Box::new).collect::<SmallIter<_>>();
+        assert_eq!(iter.skip_n(0), Ok(()));
+        assert_eq!(iter.skip_n(2), Ok(()));
+        assert_eq!(iter.nth(1), Some(Box::new(4)));
+        assert_eq!(iter.consumed(), 4);
+        assert_eq!(iter.skip_n(3), Err(NonZeroUsize::new(2).unwrap()));
+        assert_eq!(iter.nth(1), None);
+    }
+
+    #[test]
+    fn last_and_fold() {
+        let iter = (1..=5).map(Box::new).collect::<SmallIter<_>>();
+        assert_eq!(iter.clone().last(), Some(Box::new(5)));
+        assert_eq!(iter.clone().max(), Some(Box::new(5)));
+        assert_eq!(iter.fold(0, |acc, x| acc + *x), 15);
+        assert_eq!(SmallIter::<Box<i32>>::default().last(), None);
+        let mut count = 0;
+        vec![(); 3].into_small_iter().for_each(|()| count += 1);
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn seek() {
+        let mut iter = (0..100).map(|x| Box::new(x * 2)).collect::<SmallIter<_>>();
+        assert_eq!(iter.seek(&Box::new(0)), 0);
+        assert_eq!(iter.seek(&Box::new(7)), 4);
+        assert_eq!(iter.peek(), Some(&Box::new(8)));
+        assert_eq!(iter.seek_by_key(&100, |x| **x), 46);
+        assert_eq!(iter.peek(), Some(&Box::new(100)));
+        assert_eq!(iter.advance_to_partition_point(|_| true), 50);
+        assert_eq!(iter.seek(&Box::new(1000)), 0);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn seek_all_lengths() {
+        for len in 0..20 {
+            for key in 0..=len {
+                let mut iter = (0..len).collect::<SmallIter<_>>();
+                assert_eq!(iter.seek(&key), key);
+                assert_eq!(iter.len(), len - key);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "nightly")]
+    fn may_dangle() {
+        // `x` is dropped before the iterators, which still contain references
+        // to it. This compiles because the iterators' `Drop` impls don't
+        // access the references.
+        let (_vec_iter, _small_iter);
+        let x = Box::new(1);
+        _vec_iter = vec![&x].into_iter();
+        _small_iter = vec![&x].into_small_iter();
+    }
+
+    #[test]
+    fn vec_deque() {
+        let mut iter = (1..=4).map(Box::new).collect::<SmallIter<_>>();
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        let start = iter.allocation().0.as_ptr();
+        let mut deque = VecDeque::from(iter);
+        assert_eq!(deque.as_slices().0.as_ptr(), start);
+        assert_eq!(deque.capacity(), 4);
+        deque.push_front(Box::new(0));
+        let iter = SmallIter::from(deque);
+        assert_eq!(iter.allocation().0.as_ptr(), start);
+        assert_eq!(iter.as_slice(), [0, 2, 3, 4].map(Box::new));
+    }
+
+    #[test]
+    fn std_iter() {
+        let mut iter = (1..=4).map(Box::new).collect::<SmallIter<_>>();
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        let mut std_iter = iter.into_std_iter();
+        assert_eq!(std_iter.next_back(), Some(Box::new(4)));
+        let iter = SmallIter::from(std_iter);
+        assert_eq!(iter.as_slice(), [2, 3].map(Box::new));
+    }
 }
@@ -0,0 +1,289 @@
+use core::{
+    convert::Infallible,
+    marker::PhantomData,
+    mem::{align_of, size_of, ManuallyDrop},
+    ptr,
+};
+
+use allocator_api2::alloc::Allocator;
+
+use crate::{IntoSmallIterExt, SmallIter};
+
+impl<T, A: Allocator> SmallIter<T, A> {
+    /// Converts each remaining element with `f`, reusing the allocation for
+    /// the new elements.
+    ///
+    /// `U` must have the same size and alignment as `T`. Otherwise, this is a
+    /// compile-time error.
+    ///
+    /// Each new element is written in the slot of the element it replaces,
+    /// so this never reallocates, and the returned iterator keeps the same
+    /// [`consumed`](Self::consumed) count. For `U: Copy`, the consumed slots
+    /// returned by [`consumed_slice`](SmallIter::consumed_slice) hold copies
+    /// of the first new element. If no elements remain, the allocation is
+    /// freed instead.
+    ///
+    /// Unlike with `vec::IntoIter`, `iter.map(f).collect::<Vec<_>>()` never
+    /// reuses the allocation of a `SmallIter`, even with the `nightly`
+    /// feature, so use this instead.
+    ///
+    /// If `f` panics, the elements that were already converted and the
+    /// elements that weren't are dropped, and the allocation is freed.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1_u32, 2, 3].into_small_iter();
+    /// assert_eq!(iter.next(), Some(1));
+    /// let iter = iter.map_in_place(|x| x as f32 / 2.0);
+    /// assert_eq!(iter.as_slice(), &[1.0, 1.5]);
+    /// assert_eq!(iter.consumed(), 1);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// // `u64` is larger than `u32`.
+    /// let iter = vec![1_u32, 2, 3].into_small_iter().map_in_place(u64::from);
+    /// ```
+    pub fn map_in_place<U, F>(self, mut f: F) -> SmallIter<U, A>
+    where
+        F: FnMut(T) -> U,
+    {
+        match self.try_map_in_place(|element| Ok::<U, Infallible>(f(element))) {
+            Ok(iter) => iter,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Converts each remaining element with `f`, reusing the allocation for
+    /// the new elements, and stops at the first error.
+    ///
+    /// If `f` returns an error, all the elements are dropped, the allocation
+    /// is freed, and the error is returned. Otherwise, this is the same as
+    /// [`map_in_place`](Self::map_in_place).
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let iter = vec![1_u32, 2, 3].into_small_iter();
+    /// let result = iter.try_map_in_place(|x| i32::try_from(x));
+    /// assert_eq!(result.unwrap().as_slice(), &[1, 2, 3]);
+    ///
+    /// let iter = vec![1_u32, u32::MAX].into_small_iter();
+    /// let result = iter.try_map_in_place(|x| i32::try_from(x));
+    /// assert!(result.is_err());
+    /// ```
+    pub fn try_map_in_place<U, E, F>(self, mut f: F) -> Result<SmallIter<U, A>, E>
+    where
+        F: FnMut(T) -> Result<U, E>,
+    {
+        const {
+            assert!(
+                size_of::<T>() == size_of::<U>() && align_of::<T>() == align_of::<U>(),
+                "`map_in_place` requires `T` and `U` to have the same size and alignment"
+            )
+        };
+
+        if size_of::<T>() != 0 && self.elements_len() == 0 {
+            // There is no new element to fill the consumed slots with, so
+            // free the allocation instead.
+            let mut this = ManuallyDrop::new(self);
+            let (allocation_start, capacity) = this.allocation();
+            // SAFETY: `this` is never dropped, so we take ownership of the
+            // allocator and the allocation, which has no elements left. The
+            // allocation has the same layout as a `Vec<U, A>` with capacity
+            // `capacity`, since `T` and `U` have the same size and alignment.
+            let vec = unsafe {
+                let alloc = ManuallyDrop::take(&mut this.alloc);
+                allocator_api2::vec::Vec::<U, A>::from_raw_parts_in(
+                    allocation_start.as_ptr().cast(),
+                    0,
+                    capacity,
+                    alloc,
+                )
+            };
+            return Ok(vec.into_small_iter());
+        }
+
+        /// Keeps track of the progress. If `f` panics or returns an error,
+        /// this drops the new elements and the elements that haven't been
+        /// read yet, and then `iter` frees the allocation.
+        struct Guard<T, U, A: Allocator> {
+            iter: SmallIter<T, A>,
+            source: *mut T,
+            /// The number of new elements that have been written.
+            written: usize,
+            /// The number of old elements that have been read.
+            read: usize,
+            len: usize,
+            _phantom: PhantomData<U>,
+        }
+
+        impl<T, U, A: Allocator> Drop for Guard<T, U, A> {
+            fn drop(&mut self) {
+                // SAFETY: The first `written` slots hold new elements, and
+                // the elements from `read` to `len` haven't been read yet.
+                // Then, no elements are left, so we advance past all of them
+                // without dropping them, and `iter` only frees the
+                // allocation.
+                unsafe {
+                    ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                        self.source.cast::<U>(),
+                        self.written,
+                    ));
+                    ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                        self.source.add(self.read),
+                        self.len - self.read,
+                    ));
+                    self.iter.advance_unchecked(self.len);
+                }
+                // `iter` is dropped here
+            }
+        }
+
+        let mut guard = Guard::<T, U, A> {
+            source: self.elements_ptr(),
+            len: self.elements_len(),
+            iter: self,
+            written: 0,
+            read: 0,
+            _phantom: PhantomData,
+        };
+        while guard.read < guard.len {
+            // SAFETY: The element at `read` hasn't been read yet. We count it
+            // as read before calling `f`, since `f` takes ownership of it.
+            let element = unsafe { guard.source.add(guard.read).read() };
+            guard.read += 1;
+            let new_element = f(element)?;
+            // SAFETY: The new element is written in the slot of the element
+            // it replaces, which has been read. `T` and `U` have the same
+            // size and alignment, so it fits.
+            unsafe {
+                guard
+                    .source
+                    .cast::<U>()
+                    .add(guard.written)
+                    .write(new_element);
+            }
+            guard.written += 1;
+        }
+
+        // SAFETY: All elements have been read and replaced, so we take `iter`
+        // out of the guard without running its `Drop`.
+        let iter = unsafe {
+            let guard = ManuallyDrop::new(guard);
+            ptr::read(&guard.iter)
+        };
+        let mut iter = ManuallyDrop::new(iter);
+        if size_of::<T>() != 0 {
+            // Fill the consumed slots with copies of the first new element,
+            // so that they hold valid values of `U`, as `consumed_slice`
+            // requires for `U: Copy`. These copies are never dropped.
+            let first = iter.elements_ptr().cast::<U>();
+            let allocation_start = iter.allocation().0.as_ptr().cast::<U>();
+            for i in 0..iter.consumed_len() {
+                // SAFETY: The consumed slots are in the allocation, before
+                // `first`, and don't hold anything that needs to be dropped.
+                unsafe { ptr::copy_nonoverlapping(first, allocation_start.add(i), 1) };
+            }
+        }
+        // SAFETY: `iter` is never dropped, so we move its allocator into the
+        // new iterator. The allocation and the positions within it are
+        // unchanged, and now hold elements of type `U`, which has the same
+        // size and alignment as `T`. If there is a capacity header, it
+        // doesn't depend on the element type.
+        Ok(unsafe {
+            SmallIter {
+                elements_start: iter.elements_start.cast(),
+                allocation_start: iter.allocation_start.cast(),
+                end: iter.end.cast(),
+                alloc: ManuallyDrop::new(ManuallyDrop::take(&mut iter.alloc)),
+                _phantom: PhantomData,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IntoSmallIterExt, SmallIter};
+    use alloc::{boxed::Box, vec, vec::Vec};
+
+    #[test]
+    fn map_in_place() {
+        let iter = (0..=4).map(Box::new).collect::<SmallIter<_>>();
+        let start = iter.allocation().0;
+        let mut iter = iter.map_in_place(|x| Some(Box::new(*x)));
+        assert_eq!(iter.allocation().0, start.cast());
+        assert_eq!(iter.next(), Some(Some(Box::new(0))));
+        assert_eq!(iter.next(), Some(Some(Box::new(1))));
+        let iter = iter.map_in_place(|x| x.map(|x| Box::new(*x * 10)));
+        assert_eq!(iter.allocation(), (start.cast(), 5));
+        assert_eq!(iter.consumed(), 2);
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            [20, 30, 40].map(|x| Some(Box::new(x)))
+        );
+    }
+
+    #[test]
+    fn map_in_place_consumed_slots() {
+        let mut iter = vec![1_u32, 2, 3].into_small_iter();
+        let start = iter.allocation().0;
+        assert_eq!(iter.next(), Some(1));
+        let mut iter = iter.map_in_place(|x| char::from_digit(x, 10).unwrap());
+        assert_eq!(iter.allocation(), (start.cast(), 3));
+        assert_eq!(iter.consumed_slice(), &['2']);
+        iter.rewind();
+        assert_eq!(iter.as_slice(), &['2', '2', '3']);
+
+        // Nothing is left to fill the consumed slots with.
+        let mut iter = vec![1_u32, 2].into_small_iter();
+        iter.by_ref().for_each(drop);
+        let iter = iter.map_in_place(|x| char::from_digit(x, 10).unwrap());
+        assert_eq!((iter.consumed(), iter.len()), (0, 0));
+
+        let mut iter = vec![(); 3].into_small_iter();
+        iter.next();
+        let iter = iter.map_in_place(|()| [0_u8; 0]);
+        assert_eq!((iter.consumed(), iter.len()), (1, 2));
+    }
+
+    #[test]
+    fn map_in_place_keep_capacity() {
+        let mut v = Vec::with_capacity(100);
+        v.extend([1_u64, 2, 3]);
+        let mut iter = v.into_small_iter_keep_capacity();
+        assert_eq!(iter.next(), Some(1));
+        let iter = iter.map_in_place(|x| x as i64 - 5);
+        assert_eq!(iter.allocation().1, 100);
+        assert_eq!(iter.consumed_slice(), &[-3]);
+        assert_eq!(iter.as_slice(), &[-3, -2]);
+        assert_eq!(iter.into_vec().capacity(), 100);
+    }
+
+    #[test]
+    fn try_map_in_place() {
+        let iter = (1..=4).map(Box::new).collect::<SmallIter<_>>();
+        let result = iter.try_map_in_place(|x| if *x < 3 { Ok(x) } else { Err(*x) });
+        assert_eq!(result.unwrap_err(), 3);
+        let iter = vec![(); 3].into_small_iter();
+        let iter = iter.map_in_place(|()| [0_u8; 0]);
+        assert_eq!(iter.len(), 3);
+    }
+
+    #[test]
+    fn map_in_place_panic() {
+        extern crate std;
+        let mut iter = (1..=5).map(Box::new).collect::<SmallIter<_>>();
+        iter.next();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            iter.map_in_place(|x| {
+                assert_ne!(*x, 4);
+                x
+            })
+        }));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,87 @@
+//! Implementations of unstable traits, which let the standard library
+//! optimize iterator pipelines that use `SmallIter`. These match some of the
+//! implementations for `std::vec::IntoIter`.
+//!
+//! `InPlaceIterable` and `SourceIter` are not implemented. The in-place
+//! `collect` into a `Vec` also needs the standard library's private
+//! `AsVecIntoIter` trait, so they would have no effect. Use
+//! [`SmallIter::map_in_place`] to reuse the allocation instead.
+
+use core::iter::{TrustedLen, TrustedRandomAccessNoCoerce};
+
+use allocator_api2::alloc::Allocator;
+
+use crate::SmallIter;
+
+// SAFETY: `size_hint` always returns the exact number of remaining elements.
+unsafe impl<T, A: Allocator> TrustedLen for SmallIter<T, A> {}
+
+/// Types whose elements can be read without being dropped again. Like in the
+/// standard library, this is a separate trait, since specializing on `Copy`
+/// directly isn't allowed.
+#[rustc_unsafe_specialization_marker]
+pub(crate) trait NonDrop {}
+
+impl<T: Copy> NonDrop for T {}
+
+// SAFETY: `__iterator_get_unchecked` reads the element at the given index
+// from the remaining elements, which has no side effects. Since `T: Copy`,
+// the element isn't dropped again by `SmallIter`.
+//
+// Like `vec::IntoIter`, `TrustedRandomAccess` itself isn't implemented, since
+// it would allow coercing the iterator after some elements have been accessed.
+unsafe impl<T: NonDrop, A: Allocator> TrustedRandomAccessNoCoerce for SmallIter<T, A> {
+    const MAY_HAVE_SIDE_EFFECT: bool = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IntoSmallIterExt, SmallIter};
+    use alloc::{boxed::Box, vec, vec::Vec};
+    use core::iter::TrustedLen;
+
+    #[test]
+    fn trusted_len() {
+        fn assert_trusted_len<I: TrustedLen>(_: &I) {}
+
+        let iter = vec![1, 2, 3].into_small_iter();
+        assert_trusted_len(&iter);
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+    }
+
+    #[test]
+    fn zip_copy() {
+        // With `T: Copy` on both sides, `Zip` reads the elements by index
+        // through `__iterator_get_unchecked`.
+        let mut a = vec![1_u32, 2, 3, 4, 5].into_small_iter();
+        assert_eq!(a.next(), Some(1));
+        let b = vec![10_u32, 20, 30].into_small_iter();
+        let mut zipped = a.zip(b);
+        assert_eq!(zipped.size_hint(), (3, Some(3)));
+        assert_eq!(zipped.next(), Some((2, 10)));
+        assert_eq!(zipped.nth(1), Some((4, 30)));
+        assert_eq!(zipped.next(), None);
+
+        let a = vec![1_u64, 2, 3, 4].into_small_iter();
+        let b = vec![5_u64, 6, 7, 8].into_small_iter();
+        let sum = a.zip(b).map(|(x, y)| x * y).sum::<u64>();
+        assert_eq!(sum, 5 + 12 + 21 + 32);
+
+        let a = vec![(1_u8, 'a'), (2, 'b')].into_small_iter();
+        let b = vec![3_u8, 4, 5].into_small_iter();
+        let v: Vec<_> = a.zip(b).collect();
+        assert_eq!(v, [((1, 'a'), 3), ((2, 'b'), 4)]);
+
+        let a = vec![(); 4].into_small_iter();
+        let b = vec![1, 2].into_small_iter();
+        assert_eq!(a.zip(b).count(), 2);
+    }
+
+    #[test]
+    fn zip_non_copy() {
+        let boxes = (1..=3).map(Box::new).collect::<SmallIter<_>>();
+        let mut zipped = boxes.zip(vec![4, 5].into_small_iter());
+        assert_eq!(zipped.next(), Some((Box::new(1), 4)));
+        // Dropping this must not drop `Box::new(1)` again.
+    }
+}
@@ -0,0 +1,74 @@
+use core::ptr::NonNull;
+
+use allocator_api2::alloc::Allocator;
+
+/// A container that can give up its elements as a boxed slice.
+///
+/// Implementing this trait provides an implementation of
+/// [`IntoSmallIterExt`](crate::IntoSmallIterExt) that adopts the allocation
+/// directly, without going through a `Vec<T>`. This is implemented for
+/// `Box<[T], A>`.
+///
+/// # Safety
+///
+/// For the pointer `ptr`, length `len`, and allocator `alloc` returned by
+/// [`into_boxed_slice_parts`](Self::into_boxed_slice_parts), it must be
+/// valid to call `Box::from_raw_in(ptr::slice_from_raw_parts_mut(ptr, len), alloc)`
+/// with the `Box` type from `allocator_api2`. That is:
+/// - If `Layout::array::<Self::Item>(len)` has a non-zero size, `ptr` must
+///   have been allocated by `alloc` with exactly that layout. Otherwise,
+///   `ptr` must be suitably aligned, such as [`NonNull::dangling`].
+/// - The `len` elements starting at `ptr` must be initialized.
+/// - Ownership of the allocation and the elements is transferred to the
+///   caller.
+///
+/// For the global allocator, this is the same as for
+/// `Box::<[Self::Item]>::from_raw`.
+///
+/// ```
+/// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+/// use core::ptr::NonNull;
+/// use small_iter::allocator_api2::alloc::Global;
+/// use small_iter::{IntoBoxedSliceParts, IntoSmallIterExt};
+///
+/// struct Arena(Box<[u32]>);
+///
+/// // SAFETY: The parts come from a `Box<[u32]>`.
+/// unsafe impl IntoBoxedSliceParts for Arena {
+///     type Item = u32;
+///     type Alloc = Global;
+///
+///     fn into_boxed_slice_parts(self) -> (NonNull<u32>, usize, Global) {
+///         let len = self.0.len();
+///         let ptr = NonNull::new(Box::into_raw(self.0)).unwrap();
+///         (ptr.cast(), len, Global)
+///     }
+/// }
+///
+/// let iter = Arena(Box::new([1, 2, 3])).into_small_iter();
+/// assert_eq!(iter.as_slice(), &[1, 2, 3]);
+/// ```
+pub unsafe trait IntoBoxedSliceParts {
+    /// The type of the elements.
+    type Item;
+
+    /// The allocator that the elements are allocated with.
+    type Alloc: Allocator;
+
+    /// Consumes `self` and returns a pointer to the first element, the number
+    /// of elements, and the allocator. See the safety section of the trait.
+    fn into_boxed_slice_parts(self) -> (NonNull<Self::Item>, usize, Self::Alloc);
+}
+
+// SAFETY: The parts come from a `Box<[T], A>`.
+unsafe impl<T, A: Allocator> IntoBoxedSliceParts for allocator_api2::boxed::Box<[T], A> {
+    type Item = T;
+    type Alloc = A;
+
+    fn into_boxed_slice_parts(self) -> (NonNull<T>, usize, A) {
+        let (slice_ptr, alloc) = allocator_api2::boxed::Box::into_raw_with_allocator(self);
+        // SAFETY: `Box::into_raw_with_allocator` never returns null.
+        let ptr = unsafe { NonNull::new_unchecked(slice_ptr.cast::<T>()) };
+        (ptr, slice_ptr.len(), alloc)
+    }
+}
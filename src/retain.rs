@@ -0,0 +1,280 @@
+use core::ptr;
+
+use allocator_api2::alloc::Allocator;
+
+use crate::SmallIter;
+
+/// These methods remove some of the remaining elements in place, like the
+/// methods of the same names on `Vec<T>`.
+///
+/// The kept elements are moved towards the end of the allocation, so the
+/// removed elements count as [consumed](SmallIter::consumed). This means
+/// that afterwards, [`position`](SmallIter::position) and
+/// [`enumerate_absolute`](SmallIter::enumerate_absolute) no longer give
+/// indices in the original sequence of elements. For `T: Copy`, the consumed
+/// slots that [`consumed_slice`](SmallIter::consumed_slice) and
+/// [`rewind`](SmallIter::rewind) expose hold unspecified copies of removed or
+/// kept elements.
+impl<T, A: Allocator> SmallIter<T, A> {
+    /// Keeps only the remaining elements for which `f` returns `true`.
+    ///
+    /// The elements are visited in order. If `f` or dropping an element
+    /// panics, the elements that haven't been visited yet are kept.
+    ///
+    /// Each removed element increases [`position`](Self::position) by one,
+    /// so the kept elements no longer have their original indices.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 2, 3, 4, 5].into_small_iter();
+    /// iter.retain(|&x| x % 2 == 1);
+    /// assert_eq!(iter.as_slice(), &[1, 3, 5]);
+    /// assert_eq!(iter.original_len(), 5);
+    /// assert_eq!(iter.position(), 2);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|element| f(element));
+    }
+
+    /// Keeps only the remaining elements for which `f` returns `true`,
+    /// passing a mutable reference to each element.
+    ///
+    /// See [`retain`](Self::retain).
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.retain_impl(|element, _| f(element));
+    }
+
+    /// Removes consecutive remaining elements that are equal.
+    ///
+    /// ```
+    /// use small_iter::IntoSmallIterExt;
+    ///
+    /// let mut iter = vec![1, 1, 2, 3, 3, 3, 1].into_small_iter();
+    /// iter.dedup();
+    /// assert_eq!(iter.as_slice(), &[1, 2, 3, 1]);
+    /// ```
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    /// Removes consecutive remaining elements that have equal keys.
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: PartialEq,
+        F: FnMut(&mut T) -> K,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes consecutive remaining elements for which `same_bucket`
+    /// returns `true`.
+    ///
+    /// Like [`Vec::dedup_by`](alloc::vec::Vec::dedup_by), `same_bucket` is
+    /// passed the element to check, and the previous element that was kept.
+    /// If it returns `true`, the element is removed.
+    ///
+    /// As with [`retain`](Self::retain), the removed elements count as
+    /// consumed, so the consumed slots no longer match the original
+    /// sequence.
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        self.retain_impl(|element, previous| match previous {
+            Some(previous) => !same_bucket(element, previous),
+            None => true,
+        });
+    }
+
+    /// Keeps only the remaining elements for which `f` returns `true`. `f`
+    /// is also passed the last element that was kept, if any.
+    fn retain_impl<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T, Option<&mut T>) -> bool,
+    {
+        /// Keeps track of the progress, and moves the elements into place
+        /// when dropped, even if `f` or dropping an element panics.
+        struct Guard<'a, T, A: Allocator> {
+            iter: &'a mut SmallIter<T, A>,
+            start: *mut T,
+            /// The number of elements that have been visited.
+            processed: usize,
+            /// The number of visited elements that have been dropped.
+            deleted: usize,
+        }
+
+        impl<T, A: Allocator> Drop for Guard<'_, T, A> {
+            fn drop(&mut self) {
+                if self.deleted == 0 {
+                    return;
+                }
+                // The kept elements are at the front of the visited part. The
+                // elements that haven't been visited are still at the end, so
+                // moving the kept elements right before them closes the gap.
+                //
+                // SAFETY: Both ranges are within the remaining elements, and
+                // `ptr::copy` handles the overlap. Afterwards, the first
+                // `deleted` slots don't contain elements, so we advance past
+                // them without dropping them.
+                unsafe {
+                    ptr::copy(
+                        self.start,
+                        self.start.add(self.deleted),
+                        self.processed - self.deleted,
+                    );
+                    self.iter.advance_unchecked(self.deleted);
+                }
+            }
+        }
+
+        let len = self.elements_len();
+        let mut g = Guard {
+            start: self.elements_ptr(),
+            iter: self,
+            processed: 0,
+            deleted: 0,
+        };
+        while g.processed < len {
+            // SAFETY: The element at `processed` hasn't been visited yet, so
+            // it's still initialized. The last kept element is at a lower
+            // index, so the references don't overlap.
+            let (current, previous) = unsafe {
+                let kept = g.processed - g.deleted;
+                let previous = kept.checked_sub(1).map(|i| &mut *g.start.add(i));
+                (&mut *g.start.add(g.processed), previous)
+            };
+            if !f(current, previous) {
+                g.processed += 1;
+                g.deleted += 1;
+                // SAFETY: The element is counted as deleted, so it won't be
+                // used again, even if dropping it panics.
+                unsafe { ptr::drop_in_place(current) };
+                continue;
+            }
+            if g.deleted > 0 {
+                // SAFETY: The slot at `processed - deleted` is a hole, since
+                // its element was either dropped or moved.
+                unsafe {
+                    ptr::copy_nonoverlapping(
+                        g.start.add(g.processed),
+                        g.start.add(g.processed - g.deleted),
+                        1,
+                    );
+                }
+            }
+            g.processed += 1;
+        }
+        // g is dropped here
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IntoSmallIterExt, SmallIter};
+    use alloc::{boxed::Box, vec, vec::Vec};
+
+    #[test]
+    fn retain_consumed_slots() {
+        let retained = || {
+            let mut iter = vec![10_u8, 11, 12, 13, 14].into_small_iter();
+            iter.retain(|x| x % 2 == 0);
+            iter
+        };
+        let mut iter = retained();
+        assert_eq!(iter.as_slice(), &[10, 12, 14]);
+        // The removed elements count as consumed, so the indices are shifted.
+        assert_eq!(iter.position(), 2);
+        assert_eq!(iter.consumed_slice().len(), 2);
+        iter.rewind();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(&iter.as_slice()[2..], &[10, 12, 14]);
+        let v: Vec<_> = retained().enumerate_absolute().collect();
+        assert_eq!(v, [(2, 10), (3, 12), (4, 14)]);
+    }
+
+    #[test]
+    fn dedup_consumed_slots() {
+        let mut iter = vec![1_u8, 1, 1, 2].into_small_iter();
+        assert_eq!(iter.next(), Some(1));
+        iter.dedup();
+        assert_eq!(iter.as_slice(), &[1, 2]);
+        assert_eq!(iter.position(), 2);
+        // The slot of the consumed element is left alone.
+        assert_eq!(iter.consumed_slice()[0], 1);
+        iter.rewind();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(&iter.as_slice()[2..], &[1, 2]);
+    }
+
+    #[test]
+    fn retain() {
+        let mut iter = (1..=6).map(Box::new).collect::<SmallIter<_>>();
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        let mut visited = Vec::new();
+        iter.retain(|x| {
+            visited.push(**x);
+            **x % 2 == 0
+        });
+        assert_eq!(visited, [2, 3, 4, 5, 6]);
+        assert_eq!(iter.as_slice(), &[Box::new(2), Box::new(4), Box::new(6)]);
+        assert_eq!((iter.consumed(), iter.original_len()), (3, 6));
+        iter.retain_mut(|x| {
+            **x += 1;
+            true
+        });
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            [Box::new(3), Box::new(5), Box::new(7)]
+        );
+    }
+
+    #[test]
+    fn retain_panic() {
+        extern crate std;
+        let mut iter = (1..=6).map(Box::new).collect::<SmallIter<_>>();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            iter.retain(|x| {
+                assert_ne!(**x, 4);
+                **x % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(
+            iter.as_slice(),
+            &[Box::new(2), Box::new(4), Box::new(5), Box::new(6)]
+        );
+    }
+
+    #[test]
+    fn retain_zst() {
+        let mut iter = vec![(); 5].into_small_iter();
+        let mut keep = false;
+        iter.retain(|()| {
+            keep = !keep;
+            keep
+        });
+        assert_eq!((iter.consumed(), iter.len()), (2, 3));
+    }
+
+    #[test]
+    fn dedup() {
+        let mut iter = [1, 1, 2, 3, 3, 3, 1]
+            .map(Box::new)
+            .into_iter()
+            .collect::<SmallIter<_>>();
+        iter.dedup();
+        assert_eq!(iter.as_slice(), [1, 2, 3, 1].map(Box::new));
+        iter.dedup_by_key(|x| **x % 2);
+        assert_eq!(iter.as_slice(), [1, 2, 3].map(Box::new));
+    }
+}
@@ -0,0 +1,253 @@
+use alloc::{
+    boxed::Box,
+    fmt::{self, Debug},
+    vec::Vec,
+};
+use core::{
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr::{self, NonNull},
+    slice,
+};
+
+/// An iterator that moves out of a `Vec<T>` or `Box<[T]>` with at most
+/// `u32::MAX` elements.
+///
+/// This struct is created with `TryFrom<Box<[T]>>` or `TryFrom<Vec<T>>`,
+/// which fail if there are more than `u32::MAX` elements.
+///
+/// This iterator is represented as a pointer and two `u32`s, which is 2
+/// pointers on 64-bit targets.
+///
+/// ```
+/// use small_iter::SmallIter32;
+///
+/// let mut iter = SmallIter32::try_from(vec![1, 2, 3]).unwrap();
+/// # #[cfg(target_pointer_width = "64")]
+/// assert_eq!(size_of_val(&iter), 2 * size_of::<usize>());
+/// assert_eq!(iter.next(), Some(1));
+/// assert_eq!(iter.as_slice(), &[2, 3]);
+/// ```
+pub struct SmallIter32<T> {
+    /*
+    - The allocation is a `Box<[T]>` of length `len` starting at `allocation_start`
+    - SAFETY invariant: the elements from index `consumed` to index `len` are
+      initialized
+    - `consumed <= len`
+
+    This works without special cases for ZSTs, since pointer arithmetic on
+    them is a no-op.
+     */
+    allocation_start: NonNull<T>,
+    consumed: u32,
+    len: u32,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> SmallIter32<T> {
+    /// Returns the remaining elements in the iterator as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.elements_start(), self.elements_len()) }
+    }
+
+    /// Returns the remaining elements in the iterator as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.elements_start(), self.elements_len()) }
+    }
+
+    /// Returns a pointer to the first remaining element.
+    fn elements_start(&self) -> *mut T {
+        // SAFETY: `consumed <= len`, so this is within the allocation.
+        unsafe { self.allocation_start.as_ptr().add(self.consumed as usize) }
+    }
+
+    /// Returns the number of elements remaining in the iterator.
+    fn elements_len(&self) -> usize {
+        (self.len - self.consumed) as usize
+    }
+}
+
+unsafe impl<T: Send> Send for SmallIter32<T> {}
+unsafe impl<T: Sync> Sync for SmallIter32<T> {}
+
+impl<T> Iterator for SmallIter32<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.consumed == self.len {
+            None
+        } else {
+            // SAFETY: the element is initialized as per the invariant.
+            let element = unsafe { self.elements_start().read() };
+            self.consumed += 1;
+            Some(element)
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.elements_len();
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.elements_len()
+    }
+}
+
+impl<T> ExactSizeIterator for SmallIter32<T> {}
+
+impl<T> FusedIterator for SmallIter32<T> {}
+
+impl<T: Debug> Debug for SmallIter32<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SmallIter32")
+            .field(&self.as_slice())
+            .finish()
+    }
+}
+
+impl<T> AsRef<[T]> for SmallIter32<T> {
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> AsMut<[T]> for SmallIter32<T> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T> Default for SmallIter32<T> {
+    fn default() -> Self {
+        SmallIter32 {
+            allocation_start: NonNull::dangling(),
+            consumed: 0,
+            len: 0,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone> Clone for SmallIter32<T> {
+    fn clone(&self) -> Self {
+        match <Box<[T]>>::from(self.as_slice()).try_into() {
+            Ok(iter) => iter,
+            Err(_) => unreachable!("the length of `self` fits in a `u32`"),
+        }
+    }
+}
+
+/// Fails if the slice has more than `u32::MAX` elements, returning the slice
+/// back.
+impl<T> TryFrom<Box<[T]>> for SmallIter32<T> {
+    type Error = Box<[T]>;
+
+    fn try_from(slice: Box<[T]>) -> Result<Self, Self::Error> {
+        let Ok(len) = u32::try_from(slice.len()) else {
+            return Err(slice);
+        };
+        // SAFETY: `Box::into_raw` never returns null.
+        let allocation_start = unsafe { NonNull::new_unchecked(Box::into_raw(slice).cast::<T>()) };
+        Ok(SmallIter32 {
+            allocation_start,
+            consumed: 0,
+            len,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// Fails if the vector has more than `u32::MAX` elements, returning the
+/// vector back.
+///
+/// Note that if there is excess capacity in the vector, this will first
+/// shrink the allocation to fit the existing elements. Depending on the
+/// allocator, this may reallocate.
+impl<T> TryFrom<Vec<T>> for SmallIter32<T> {
+    type Error = Vec<T>;
+
+    fn try_from(vec: Vec<T>) -> Result<Self, Self::Error> {
+        if u32::try_from(vec.len()).is_err() {
+            return Err(vec);
+        }
+        vec.into_boxed_slice().try_into().map_err(Vec::from)
+    }
+}
+
+impl<T> Drop for SmallIter32<T> {
+    fn drop(&mut self) {
+        struct DropGuard<'a, T>(&'a mut SmallIter32<T>);
+
+        impl<T> Drop for DropGuard<'_, T> {
+            // Drop the Box allocation, but not the contained elements in the slice.
+            fn drop(&mut self) {
+                let slice_ptr: *mut [ManuallyDrop<T>] = ptr::slice_from_raw_parts_mut(
+                    self.0.allocation_start.as_ptr().cast(),
+                    self.0.len as usize,
+                );
+                // SAFETY: We reconstruct the original `Box<[T]>`, but as a
+                // `Box<[ManuallyDrop<T>]>`, and then drop it.
+                unsafe { drop(Box::from_raw(slice_ptr)) };
+            }
+        }
+
+        let guard = DropGuard(self);
+        // SAFETY: We drop only the initialized elements.
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                guard.0.elements_start(),
+                guard.0.elements_len(),
+            ));
+        }
+        // guard is dropped here
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn basic_exhaust() {
+        let mut iter = SmallIter32::try_from(vec![Box::new(1), Box::new(2), Box::new(3)]).unwrap();
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        assert_eq!(iter.as_slice(), &[Box::new(2), Box::new(3)]);
+        assert_eq!(iter.next(), Some(Box::new(2)));
+        assert_eq!(iter.next(), Some(Box::new(3)));
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        assert_eq!(iter.as_slice(), &[]);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn basic_partial() {
+        let mut iter = SmallIter32::try_from(vec![Box::new(1), Box::new(2), Box::new(3)]).unwrap();
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        assert_eq!(iter.clone().collect::<Vec<_>>(), [Box::new(2), Box::new(3)]);
+        // Drop the iterator here
+    }
+
+    #[test]
+    fn basic_partial_zst() {
+        let mut iter = SmallIter32::try_from(vec![(); 3]).unwrap();
+        assert_eq!(iter.next(), Some(()));
+        assert_eq!(iter.as_slice(), &[(), ()]);
+        // Drop the iterator here
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn too_long() {
+        let len = u32::MAX as usize + 1;
+        let v = SmallIter32::try_from(vec![(); len]).unwrap_err();
+        assert_eq!(v.len(), len);
+    }
+}
@@ -0,0 +1,334 @@
+use alloc::{
+    boxed::Box,
+    fmt::{self, Debug},
+    vec::Vec,
+};
+use core::{
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem::{align_of, size_of, ManuallyDrop},
+    ptr::{self, NonNull},
+    slice,
+};
+
+use crate::{IntoSmallIterExt, SmallIter};
+
+/// A 2-pointer iterator that moves out of a `Vec<T>` or `Box<[T]>`, for
+/// element types that are at least as large as a pointer.
+///
+/// This struct is created with `From<Box<[T]>>` or `From<Vec<T>>`.
+///
+/// Once the first element is consumed, its slot in the allocation is unused,
+/// so this iterator stores the start of the allocation there instead of in
+/// the iterator itself. As a result, it is one pointer smaller than
+/// [`SmallIter`]. In exchange, every call to `next` moves the stored pointer
+/// to the newly freed slot.
+///
+/// `T` must have at least the size and alignment of a pointer. Otherwise,
+/// creating a `SmallerIter<T>` is a compile-time error. Use [`SmallIter`] for
+/// such types instead.
+///
+/// ```
+/// use small_iter::SmallerIter;
+///
+/// let mut iter = SmallerIter::from(vec![1_u64, 2, 3]);
+/// assert_eq!(size_of_val(&iter), 2 * size_of::<usize>());
+/// assert_eq!(iter.next(), Some(1));
+/// assert_eq!(iter.as_slice(), &[2, 3]);
+/// ```
+///
+/// ```compile_fail
+/// use small_iter::SmallerIter;
+///
+/// // `u8` is smaller than a pointer.
+/// let iter = SmallerIter::from(vec![1_u8, 2, 3]);
+/// ```
+pub struct SmallerIter<T> {
+    /*
+    - The remaining elements are at `elements_start..end`, where `end` is the
+      `end` field with its lowest bit cleared
+    - SAFETY invariant: the memory from `elements_start` to `end` is initialized
+    - If the lowest bit of the `end` field is 0, then no elements have been
+      consumed, and the allocation is `elements_start..end`
+    - If the lowest bit of the `end` field is 1, then the slot just before
+      `elements_start` contains a `NonNull<T>` pointing to the start of the
+      allocation, and the allocation is `allocation_start..end`
+
+    The lowest bit of a pointer to `T` is always 0, since `T` has at least the
+    alignment of a pointer, which is checked to be at least 2.
+     */
+    elements_start: NonNull<T>,
+    end: *const T,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> SmallerIter<T> {
+    /// Returns the remaining elements in the iterator as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.elements_start.as_ptr(), self.elements_len()) }
+    }
+
+    /// Returns the remaining elements in the iterator as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.elements_start.as_ptr(), self.elements_len()) }
+    }
+
+    /// Returns whether the start of the allocation is stored just before
+    /// `elements_start`.
+    fn is_allocation_start_stored(&self) -> bool {
+        self.end as usize & 1 == 1
+    }
+
+    /// Returns the end of the remaining elements, without the tag bit.
+    fn end(&self) -> *const T {
+        if self.is_allocation_start_stored() {
+            self.end.wrapping_byte_sub(1)
+        } else {
+            self.end
+        }
+    }
+
+    /// Returns the number of elements remaining in the iterator.
+    fn elements_len(&self) -> usize {
+        // SAFETY: `elements_start..end` is from the same allocation.
+        unsafe { self.end().offset_from(self.elements_start.as_ptr()) as usize }
+    }
+
+    /// Returns the start of the allocation.
+    fn allocation_start(&self) -> NonNull<T> {
+        if self.is_allocation_start_stored() {
+            // SAFETY: The slot just before `elements_start` contains the start
+            // of the allocation, as per the invariant.
+            unsafe {
+                self.elements_start
+                    .as_ptr()
+                    .sub(1)
+                    .cast::<NonNull<T>>()
+                    .read()
+            }
+        } else {
+            self.elements_start
+        }
+    }
+
+    /// Returns the start of the allocation, and the number of elements in
+    /// the allocation, including uninitialized elements.
+    fn allocation(&self) -> (NonNull<T>, usize) {
+        let allocation_start = self.allocation_start();
+        // SAFETY: `allocation_start..end` is from the same allocation.
+        let len = unsafe { self.end().offset_from(allocation_start.as_ptr()) as usize };
+        (allocation_start, len)
+    }
+}
+
+unsafe impl<T: Send> Send for SmallerIter<T> {}
+unsafe impl<T: Sync> Sync for SmallerIter<T> {}
+
+impl<T> Iterator for SmallerIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = self.end();
+        if ptr::eq(self.elements_start.as_ptr(), end) {
+            return None;
+        }
+        let allocation_start = self.allocation_start();
+        // SAFETY: the memory is initialized as per the invariant.
+        let element = unsafe { self.elements_start.as_ptr().read() };
+        // SAFETY: The slot we just read from is now unused, and has room for
+        // a pointer. We've checked that we're not at the end, so we can
+        // advance by 1.
+        unsafe {
+            self.elements_start
+                .as_ptr()
+                .cast::<NonNull<T>>()
+                .write(allocation_start);
+            self.elements_start = NonNull::new_unchecked(self.elements_start.as_ptr().add(1));
+        }
+        self.end = end.wrapping_byte_add(1);
+        Some(element)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.elements_len();
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.elements_len()
+    }
+}
+
+impl<T> ExactSizeIterator for SmallerIter<T> {}
+
+impl<T> FusedIterator for SmallerIter<T> {}
+
+impl<T: Debug> Debug for SmallerIter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SmallerIter")
+            .field(&self.as_slice())
+            .finish()
+    }
+}
+
+impl<T> AsRef<[T]> for SmallerIter<T> {
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> AsMut<[T]> for SmallerIter<T> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T> Default for SmallerIter<T> {
+    fn default() -> Self {
+        <Box<[T]>>::default().into()
+    }
+}
+
+impl<T: Clone> Clone for SmallerIter<T> {
+    fn clone(&self) -> Self {
+        <Box<[T]>>::from(self.as_slice()).into()
+    }
+}
+
+impl<T> From<Box<[T]>> for SmallerIter<T> {
+    fn from(slice: Box<[T]>) -> Self {
+        const {
+            assert!(
+                size_of::<T>() >= size_of::<NonNull<T>>()
+                    && align_of::<T>() >= align_of::<NonNull<T>>()
+                    && align_of::<T>() >= 2,
+                "`SmallerIter<T>` requires `T` to have at least the size and alignment of a pointer"
+            )
+        };
+        let len = slice.len();
+        let first_element_ptr = Box::into_raw(slice).cast::<T>();
+        // SAFETY: We set `elements_start` and `end` to be the beginning and
+        // end of the slice. The elements in between are initialized.
+        unsafe {
+            SmallerIter {
+                elements_start: NonNull::new_unchecked(first_element_ptr),
+                end: first_element_ptr.add(len),
+                _phantom: PhantomData,
+            }
+        }
+    }
+}
+
+/// Note that if there is excess capacity in the vector, this will first
+/// shrink the allocation to fit the existing elements. Depending on the
+/// allocator, this may reallocate.
+impl<T> From<Vec<T>> for SmallerIter<T> {
+    fn from(vec: Vec<T>) -> Self {
+        vec.into_boxed_slice().into()
+    }
+}
+
+/// Collects the elements into a [`SmallerIter`], without reallocating if the
+/// iterator reports an exact size. See the `FromIterator` impl of
+/// [`SmallIter`].
+impl<T> FromIterator<T> for SmallerIter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .collect::<SmallIter<T>>()
+            .into_boxed_slice()
+            .into()
+    }
+}
+
+/// If elements have been consumed, the remaining elements are moved to the
+/// start of the allocation, and the excess capacity is handled as in
+/// [`IntoSmallIterExt::into_small_iter_keep_capacity`]. This may reallocate.
+impl<T> From<SmallerIter<T>> for SmallIter<T> {
+    fn from(iter: SmallerIter<T>) -> Self {
+        let iter = ManuallyDrop::new(iter);
+        let (allocation_start, allocation_len) = iter.allocation();
+        let len = iter.elements_len();
+        // The consumed slots contain copies of the start of the allocation,
+        // but `SmallIter` allows reading consumed elements if `T` is `Copy`.
+        // So we can't keep them as consumed elements.
+        //
+        // SAFETY: Both ranges are in the same allocation, and the remaining
+        // elements are initialized. `ptr::copy` handles the overlap. The
+        // allocation has the same layout as a `Vec<T>` with capacity
+        // `allocation_len`, and `iter` is never dropped.
+        let vec = unsafe {
+            ptr::copy(iter.elements_start.as_ptr(), allocation_start.as_ptr(), len);
+            Vec::from_raw_parts(allocation_start.as_ptr(), len, allocation_len)
+        };
+        vec.into_small_iter_keep_capacity()
+    }
+}
+
+impl<T> Drop for SmallerIter<T> {
+    fn drop(&mut self) {
+        struct DropGuard<'a, T>(&'a mut SmallerIter<T>);
+
+        impl<T> Drop for DropGuard<'_, T> {
+            // Drop the Box allocation, but not the contained elements in the slice.
+            fn drop(&mut self) {
+                let (allocation_start, allocation_len) = self.0.allocation();
+                let slice_ptr: *mut [ManuallyDrop<T>] =
+                    ptr::slice_from_raw_parts_mut(allocation_start.as_ptr().cast(), allocation_len);
+                // SAFETY: We reconstruct the original `Box<[T]>`, but as a
+                // `Box<[ManuallyDrop<T>]>`, and then drop it.
+                unsafe { drop(Box::from_raw(slice_ptr)) };
+            }
+        }
+
+        let guard = DropGuard(self);
+        // SAFETY: We drop only the initialized elements.
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                guard.0.elements_start.as_ptr(),
+                guard.0.elements_len(),
+            ));
+        }
+        // guard is dropped here
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn basic_exhaust() {
+        let mut iter = SmallerIter::from(vec![Box::new(1), Box::new(2), Box::new(3)]);
+        assert_eq!(size_of_val(&iter), 2 * size_of::<usize>());
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        assert_eq!(iter.as_slice(), &[Box::new(2), Box::new(3)]);
+        assert_eq!(iter.next(), Some(Box::new(2)));
+        assert_eq!(iter.next(), Some(Box::new(3)));
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        assert_eq!(iter.as_slice(), &[]);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn basic_partial() {
+        let mut iter = SmallerIter::from(vec![Box::new(1), Box::new(2), Box::new(3)]);
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        assert_eq!(iter.clone().collect::<Vec<_>>(), [Box::new(2), Box::new(3)]);
+        // Drop the iterator here
+    }
+
+    #[test]
+    fn into_small_iter() {
+        let mut iter: SmallerIter<u64> = (1..4).collect();
+        assert_eq!(iter.next(), Some(1));
+        let iter = SmallIter::from(iter);
+        assert_eq!(iter.as_slice(), &[2, 3]);
+        assert_eq!(iter.consumed_slice(), &[]);
+    }
+}
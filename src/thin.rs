@@ -0,0 +1,330 @@
+use alloc::{
+    alloc::{alloc, dealloc, handle_alloc_error, Layout},
+    boxed::Box,
+    fmt::{self, Debug},
+    vec::Vec,
+};
+use core::{
+    iter::FusedIterator,
+    marker::PhantomData,
+    ptr::{self, NonNull},
+    slice,
+};
+
+/// A 1-pointer iterator that moves out of a `Vec<T>` or `Box<[T]>`.
+///
+/// This struct is created with `From<Box<[T]>>` or `From<Vec<T>>`.
+///
+/// The number of elements and the number of consumed elements are stored in
+/// a header at the start of the allocation, similarly to `thin_vec::ThinVec`.
+/// As a result, this iterator is represented as a single pointer.
+///
+/// In exchange:
+/// - Creating a `ThinSmallIter` moves the elements into a new allocation,
+///   unless there are no elements.
+/// - Every call to `next` reads and writes the header in the allocation,
+///   which is slower than [`SmallIter`](crate::SmallIter), especially if the
+///   allocation is not in the cache.
+///
+/// ```
+/// use small_iter::ThinSmallIter;
+///
+/// let mut iter = ThinSmallIter::from(vec![1, 2, 3]);
+/// assert_eq!(size_of_val(&iter), size_of::<usize>());
+/// assert_eq!(iter.next(), Some(1));
+/// assert_eq!(iter.as_slice(), &[2, 3]);
+/// ```
+pub struct ThinSmallIter<T> {
+    /*
+    - `header` points to a `Header`, which is followed by `header.len`
+      elements (with padding in between if needed). See `ThinSmallIter::layout`.
+    - SAFETY invariant: the elements from index `header.consumed` to index
+      `header.len` are initialized
+    - If `header.len == 0`, then `header` may point to `EMPTY_HEADER`,
+      which must not be written to or deallocated.
+     */
+    header: NonNull<Header>,
+    _phantom: PhantomData<T>,
+}
+
+#[repr(C)]
+struct Header {
+    len: usize,
+    consumed: usize,
+}
+
+static EMPTY_HEADER: Header = Header {
+    len: 0,
+    consumed: 0,
+};
+
+impl<T> ThinSmallIter<T> {
+    /// Returns the remaining elements in the iterator as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        let Header { len, consumed } = *self.header();
+        // SAFETY: the elements from `consumed` to `len` are initialized.
+        unsafe { slice::from_raw_parts(self.elements_start().add(consumed), len - consumed) }
+    }
+
+    /// Returns the remaining elements in the iterator as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        let Header { len, consumed } = *self.header();
+        // SAFETY: the elements from `consumed` to `len` are initialized.
+        unsafe { slice::from_raw_parts_mut(self.elements_start().add(consumed), len - consumed) }
+    }
+
+    /// Returns the layout of an allocation with `len` elements, and the
+    /// offset of the first element.
+    fn layout(len: usize) -> (Layout, usize) {
+        Layout::array::<T>(len)
+            .and_then(|elements| Layout::new::<Header>().extend(elements))
+            .expect("capacity overflow")
+    }
+
+    /// Allocates room for `len` elements. The header is initialized with all
+    /// elements marked as consumed, so the caller doesn't need to initialize
+    /// any elements.
+    fn allocate(len: usize) -> Self {
+        if len == 0 {
+            return ThinSmallIter {
+                header: NonNull::from(&EMPTY_HEADER),
+                _phantom: PhantomData,
+            };
+        }
+        let (layout, _) = Self::layout(len);
+        // SAFETY: `layout` has a non-zero size, since it includes the header.
+        let header = unsafe { alloc(layout) }.cast::<Header>();
+        let Some(header) = NonNull::new(header) else {
+            handle_alloc_error(layout)
+        };
+        // SAFETY: `header` is freshly allocated with room for a `Header`.
+        unsafe { header.as_ptr().write(Header { len, consumed: len }) };
+        ThinSmallIter {
+            header,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn header(&self) -> &Header {
+        // SAFETY: `header` always points to an initialized `Header`.
+        unsafe { self.header.as_ref() }
+    }
+
+    /// Returns a pointer to the first element of the allocation, which may be
+    /// consumed.
+    fn elements_start(&self) -> *mut T {
+        if self.header().len == 0 {
+            NonNull::dangling().as_ptr()
+        } else {
+            let (_, offset) = Self::layout(self.header().len);
+            // SAFETY: The elements are at `offset` in the allocation.
+            unsafe { self.header.as_ptr().byte_add(offset).cast() }
+        }
+    }
+
+    /// Returns the number of elements remaining in the iterator.
+    fn elements_len(&self) -> usize {
+        let Header { len, consumed } = *self.header();
+        len - consumed
+    }
+}
+
+unsafe impl<T: Send> Send for ThinSmallIter<T> {}
+unsafe impl<T: Sync> Sync for ThinSmallIter<T> {}
+
+impl<T> Iterator for ThinSmallIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let Header { len, consumed } = *self.header();
+        if consumed == len {
+            None
+        } else {
+            // SAFETY: the element at `consumed` is initialized as per the
+            // invariant. The header is not `EMPTY_HEADER`, since `len > 0`.
+            unsafe {
+                let element = self.elements_start().add(consumed).read();
+                (*self.header.as_ptr()).consumed = consumed + 1;
+                Some(element)
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.elements_len();
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.elements_len()
+    }
+}
+
+impl<T> ExactSizeIterator for ThinSmallIter<T> {}
+
+impl<T> FusedIterator for ThinSmallIter<T> {}
+
+impl<T: Debug> Debug for ThinSmallIter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ThinSmallIter")
+            .field(&self.as_slice())
+            .finish()
+    }
+}
+
+impl<T> AsRef<[T]> for ThinSmallIter<T> {
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> AsMut<[T]> for ThinSmallIter<T> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T> Default for ThinSmallIter<T> {
+    fn default() -> Self {
+        Self::allocate(0)
+    }
+}
+
+impl<T: Clone> Clone for ThinSmallIter<T> {
+    fn clone(&self) -> Self {
+        let source = self.as_slice();
+        let new = Self::allocate(source.len());
+        // We clone the elements back to front, marking each element as not
+        // consumed once it's initialized. If `clone` panics, `new` is dropped,
+        // which drops only the initialized elements.
+        for (i, element) in source.iter().enumerate().rev() {
+            // SAFETY: The element at `i` is uninitialized, and the header is
+            // not `EMPTY_HEADER`, since `source` is not empty.
+            unsafe {
+                new.elements_start().add(i).write(element.clone());
+                (*new.header.as_ptr()).consumed = i;
+            }
+        }
+        new
+    }
+}
+
+/// This moves the elements into a new allocation.
+impl<T> From<Vec<T>> for ThinSmallIter<T> {
+    fn from(mut vec: Vec<T>) -> Self {
+        let len = vec.len();
+        let new = Self::allocate(len);
+        // SAFETY: We move the elements from `vec` into `new`, and then mark
+        // them as moved out of `vec` and as not consumed in `new`.
+        unsafe {
+            ptr::copy_nonoverlapping(vec.as_ptr(), new.elements_start(), len);
+            vec.set_len(0);
+            if len != 0 {
+                (*new.header.as_ptr()).consumed = 0;
+            }
+        }
+        new
+    }
+}
+
+/// This moves the elements into a new allocation.
+impl<T> From<Box<[T]>> for ThinSmallIter<T> {
+    fn from(slice: Box<[T]>) -> Self {
+        Vec::from(slice).into()
+    }
+}
+
+/// This collects the elements into a `Vec<T>` first, and then moves them
+/// into a new allocation.
+impl<T> FromIterator<T> for ThinSmallIter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<T>>().into()
+    }
+}
+
+impl<T> Drop for ThinSmallIter<T> {
+    fn drop(&mut self) {
+        struct DropGuard<'a, T>(&'a mut ThinSmallIter<T>);
+
+        impl<T> Drop for DropGuard<'_, T> {
+            // Deallocate the allocation, but not drop the contained elements.
+            fn drop(&mut self) {
+                let len = self.0.header().len;
+                if len != 0 {
+                    let (layout, _) = ThinSmallIter::<T>::layout(len);
+                    // SAFETY: The allocation was allocated with this layout.
+                    unsafe { dealloc(self.0.header.as_ptr().cast(), layout) };
+                }
+            }
+        }
+
+        let guard = DropGuard(self);
+        // SAFETY: We drop only the initialized elements.
+        unsafe {
+            ptr::drop_in_place(guard.0.as_mut_slice());
+        }
+        // guard is dropped here
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn basic_exhaust() {
+        let mut iter = ThinSmallIter::from(vec![Box::new(1), Box::new(2), Box::new(3)]);
+        assert_eq!(size_of_val(&iter), size_of::<usize>());
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        assert_eq!(iter.as_slice(), &[Box::new(2), Box::new(3)]);
+        assert_eq!(iter.next(), Some(Box::new(2)));
+        assert_eq!(iter.next(), Some(Box::new(3)));
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        assert_eq!(iter.as_slice(), &[]);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn basic_partial() {
+        let mut iter = ThinSmallIter::from(vec![Box::new(1), Box::new(2), Box::new(3)]);
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        assert_eq!(iter.clone().collect::<Vec<_>>(), [Box::new(2), Box::new(3)]);
+        // Drop the iterator here
+    }
+
+    #[test]
+    fn empty() {
+        let mut iter = ThinSmallIter::<Box<i32>>::default();
+        assert_eq!(iter.as_slice(), &[]);
+        assert_eq!(iter.next(), None);
+        let mut iter = iter.clone();
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn over_aligned() {
+        #[derive(Clone, Debug, PartialEq)]
+        #[repr(align(64))]
+        struct Aligned(u8);
+
+        let mut iter = ThinSmallIter::from(vec![Aligned(1), Aligned(2)]);
+        assert_eq!(iter.as_slice().as_ptr() as usize % 64, 0);
+        assert_eq!(iter.next(), Some(Aligned(1)));
+        assert_eq!(iter.as_slice(), &[Aligned(2)]);
+    }
+
+    #[test]
+    fn basic_partial_zst() {
+        let mut iter: ThinSmallIter<()> = [(); 3].into_iter().collect();
+        assert_eq!(iter.next(), Some(()));
+        assert_eq!(iter.clone().count(), 2);
+        assert_eq!(iter.as_slice(), &[(), ()]);
+        // Drop the iterator here
+    }
+}
@@ -0,0 +1,63 @@
+//! Conversions between `SmallIter` and `ThinVec` from the `thin-vec` crate.
+
+use alloc::vec::Vec;
+use core::ptr;
+
+use allocator_api2::alloc::Global;
+use thin_vec::ThinVec;
+
+use crate::{IntoSmallIterExt, Sealed, SmallIter};
+
+impl<T> Sealed for ThinVec<T> {}
+
+/// `ThinVec` stores its length and capacity in the same allocation as the
+/// elements, so it can't be adopted. Instead, this moves the elements into a
+/// new allocation of exactly the right size, and frees the old one.
+impl<T> IntoSmallIterExt for ThinVec<T> {
+    type Item = T;
+    type Alloc = Global;
+
+    fn into_small_iter(mut self) -> SmallIter<T> {
+        let len = self.len();
+        let mut vec = Vec::with_capacity(len);
+        // SAFETY: The elements are moved into `vec`, and `self` is emptied so
+        // that it frees its allocation without dropping them again.
+        unsafe {
+            ptr::copy_nonoverlapping(self.as_ptr(), vec.as_mut_ptr(), len);
+            self.set_len(0);
+            vec.set_len(len);
+        }
+        vec.into_small_iter()
+    }
+}
+
+/// Moves the remaining elements into a new `ThinVec` of exactly the right
+/// capacity, and frees the allocation of the iterator.
+impl<T> From<SmallIter<T>> for ThinVec<T> {
+    fn from(iter: SmallIter<T>) -> Self {
+        let mut vec = ThinVec::with_capacity(iter.len());
+        vec.extend(iter);
+        vec
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+    use thin_vec::thin_vec;
+
+    #[test]
+    fn thin_vec() {
+        let mut iter = thin_vec![Box::new(1), Box::new(2), Box::new(3)].into_small_iter();
+        assert_eq!(iter.next(), Some(Box::new(1)));
+        let vec = ThinVec::from(iter);
+        assert_eq!(vec, [Box::new(2), Box::new(3)]);
+
+        assert_eq!(ThinVec::<u32>::new().into_small_iter().len(), 0);
+        assert_eq!(ThinVec::from(SmallIter::<u32>::default()).len(), 0);
+
+        let iter = thin_vec![(); 5].into_small_iter();
+        assert_eq!(ThinVec::from(iter).len(), 5);
+    }
+}